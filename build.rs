@@ -1,3 +1,13 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
 fn main() {
     embuild::espidf::sysenv::output();
+
+    // Fallback for the clock until it is set over serial. Cargo only runs this
+    // script again when the sources change, so it's the time of that build.
+    println!("cargo:rerun-if-changed=src");
+    let build_time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since_epoch| since_epoch.as_secs());
+    println!("cargo:rustc-env=BUILD_UNIX_TIME={build_time}");
 }
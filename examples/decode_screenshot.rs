@@ -0,0 +1,48 @@
+// Saves the screenshots found in a serial log, e.g. one captured with
+// `espflash monitor | tee serial.log`, as screenshot-N.txt, .ans or .bmp:
+//
+//     cargo run --target x86_64-unknown-linux-gnu --example decode_screenshot -- serial.log
+//
+// Reads the log from stdin without an argument.
+use mousefood_esp32_demo::screenshot;
+use std::io::{self, Read};
+use std::process::ExitCode;
+use std::{env, fs};
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run() -> Result<(), Box<dyn std::error::Error>> {
+    let bytes = match env::args_os().nth(1) {
+        Some(path) => fs::read(path)?,
+        None => {
+            let mut bytes = Vec::new();
+            io::stdin().read_to_end(&mut bytes)?;
+            bytes
+        }
+    };
+    // Serial logs often start with garbage from the boot ROM's baud rate.
+    let log = String::from_utf8_lossy(&bytes);
+    let screenshots = screenshot::decode(&log)?;
+    if screenshots.is_empty() {
+        return Err("no screenshots in the log".into());
+    }
+    for (number, shot) in screenshots.iter().enumerate() {
+        let path = format!("screenshot-{}.{}", number + 1, shot.format.extension());
+        fs::write(&path, &shot.data)?;
+        println!(
+            "{path}: {} {}x{}",
+            shot.format.name(),
+            shot.width,
+            shot.height
+        );
+    }
+    Ok(())
+}
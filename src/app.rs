@@ -0,0 +1,273 @@
+use crate::battery_history::BatteryHistoryApp;
+use crate::carousel::{CarouselConfig, PlaylistError, validate_playlist};
+use crate::chart::ChartApp;
+use crate::gauge::GaugeApp;
+use crate::input::InputSource;
+use crate::metrics::MetricsSource;
+use crate::overlay::Overlay;
+use crate::ratatui_logo::RatatuiLogoApp;
+use crate::scheduler::Scheduler;
+use crate::screen::{Action, Context, Screen};
+use crate::screenshot::ScreenshotFormat;
+use crate::settings_screen::SettingsApp;
+use crate::status_bar::{self, StatusBar};
+use crate::system_monitor::SystemMonitorApp;
+use crate::tabs::TabsApp;
+use crate::transition::{Effect, Transition, composite};
+use crate::voltage::VoltageApp;
+use mousefood::prelude::*;
+use mousefood::ratatui::buffer::Buffer;
+use mousefood::ratatui::widgets::Block;
+use std::io;
+use std::time::{Duration, Instant};
+
+/// Longest wait for input while the screen is off, before the overlays are updated again.
+const SCREEN_OFF_POLL: Duration = Duration::from_secs(1);
+
+/// The demo screens in the order they are shown, used by both the firmware and the simulator.
+///
+/// `metrics` feeds the system monitor with readings of the board or the host,
+/// `chart` plots the sine waves of [`ChartApp::new`] or real sources.
+pub fn demo_screens(metrics: Box<dyn MetricsSource>, chart: ChartApp) -> Vec<Box<dyn Screen>> {
+    vec![
+        Box::new(RatatuiLogoApp::new()),
+        Box::new(TabsApp::new()),
+        Box::new(chart),
+        Box::new(GaugeApp::new()),
+        Box::new(SystemMonitorApp::new(metrics)),
+        Box::new(VoltageApp::new()),
+        Box::new(BatteryHistoryApp::new()),
+        Box::new(SettingsApp::new()),
+    ]
+}
+
+/// Owns the demo screens and cycles through them in order.
+pub struct App {
+    screens: Vec<Box<dyn Screen>>,
+    overlays: Vec<Box<dyn Overlay>>,
+    current: usize,
+    ctx: Context,
+    scheduler: Scheduler,
+    screenshot_request: Option<ScreenshotFormat>,
+    screenshot: Option<(ScreenshotFormat, Buffer)>,
+    /// Copy of the frame drawn last, where the next transition starts from.
+    last_frame: Buffer,
+    transition: Option<ActiveTransition>,
+}
+
+/// A transition in progress from the frame shown before a screen switch.
+struct ActiveTransition {
+    transition: Transition,
+    from: Buffer,
+    started: Instant,
+}
+
+impl App {
+    pub fn new(screens: Vec<Box<dyn Screen>>) -> Self {
+        assert!(!screens.is_empty(), "App needs at least one screen");
+        let mut app = Self {
+            screens,
+            overlays: Vec::new(),
+            current: 0,
+            ctx: Context::default(),
+            scheduler: Scheduler::default(),
+            screenshot_request: None,
+            screenshot: None,
+            last_frame: Buffer::default(),
+            transition: None,
+        };
+        app.screens[0].init(&mut app.ctx);
+        app
+    }
+
+    /// Replaces the default 30 FPS / 20 Hz tick scheduler.
+    pub fn with_scheduler(mut self, scheduler: Scheduler) -> Self {
+        self.scheduler = scheduler;
+        self
+    }
+
+    /// Adds an overlay drawn on top of every screen, after those added before.
+    pub fn with_overlay(mut self, overlay: Box<dyn Overlay>) -> Self {
+        self.overlays.push(overlay);
+        self
+    }
+
+    /// Index of the active screen.
+    pub fn current(&self) -> usize {
+        self.current
+    }
+
+    /// Names of the screens in the order they are shown.
+    pub fn screen_names(&self) -> Vec<&'static str> {
+        self.screens.iter().map(|screen| screen.name()).collect()
+    }
+
+    pub fn context(&self) -> &Context {
+        &self.ctx
+    }
+
+    pub fn context_mut(&mut self) -> &mut Context {
+        &mut self.ctx
+    }
+
+    /// Waits for input until the next frame is due, then runs the elapsed
+    /// ticks and draws the active screen. An event draws the frame early.
+    ///
+    /// While the screen is off nothing is drawn, the overlays are still updated
+    /// every second and a press only wakes the screen up.
+    ///
+    /// Returns the [`Action`] that switched screens, in which case nothing is drawn
+    /// and the transition starts with the next frame, or [`Action::Stay`].
+    /// The carousel moving on counts as [`Action::NextScreen`].
+    pub fn frame<B: Backend>(
+        &mut self,
+        terminal: &mut Terminal<B>,
+        input: &mut impl InputSource,
+    ) -> io::Result<Action> {
+        let now = Instant::now();
+        // A running carousel is meant to be watched, so it keeps the screen on.
+        if self.ctx.carousel.is_running(now) {
+            self.ctx.power.activity(now);
+        }
+        let mut state = self.ctx.power.update(now);
+        if !state.screen_on() {
+            // Overlays keep watching while nobody looks, e.g. for a critical battery.
+            for overlay in &mut self.overlays {
+                overlay.update(now, &mut self.ctx);
+            }
+            state = self.ctx.power.state();
+        }
+        if !state.screen_on() {
+            let timeout = self
+                .ctx
+                .power
+                .time_until_change(now)
+                .unwrap_or_default()
+                .min(SCREEN_OFF_POLL);
+            if input.wait(timeout).is_some() {
+                let now = Instant::now();
+                self.ctx.power.activity(now);
+                self.ctx.carousel.activity(now);
+                self.scheduler.reset(now);
+            }
+            return Ok(Action::Stay);
+        }
+
+        let timeout = self.scheduler.time_until_frame(now);
+        if let Some(event) = input.wait(timeout) {
+            let now = Instant::now();
+            self.ctx.power.activity(now);
+            self.ctx.carousel.activity(now);
+            // The topmost overlay gets the first chance to take the event.
+            let consumed = self
+                .overlays
+                .iter_mut()
+                .rev()
+                .any(|overlay| overlay.handle_event(event, &mut self.ctx));
+            let screen = &mut self.screens[self.current];
+            let action = if consumed {
+                Action::Stay
+            } else {
+                screen.handle_event(event, &mut self.ctx)
+            };
+            match action {
+                Action::Stay => {}
+                Action::NextScreen => {
+                    let next = (self.current + 1) % self.screens.len();
+                    self.switch_to(next, self.ctx.transitions.next);
+                    return Ok(action);
+                }
+                Action::PreviousScreen => {
+                    let len = self.screens.len();
+                    let previous = (self.current + len - 1) % len;
+                    self.switch_to(previous, self.ctx.transitions.previous);
+                    return Ok(action);
+                }
+            }
+        }
+
+        let now = Instant::now();
+        let names = self.screen_names();
+        let next = self.ctx.carousel.update(now, &names);
+        if let Some(index) = next.filter(|&index| index != self.current) {
+            self.switch_to(index, self.ctx.transitions.next);
+            return Ok(Action::NextScreen);
+        }
+
+        let dt = self.scheduler.tick_interval();
+        let (current, count) = (self.current, self.screens.len());
+        let screen = &mut self.screens[self.current];
+        for _ in 0..self.scheduler.take_ticks(now) {
+            screen.tick(dt, &mut self.ctx);
+        }
+        for overlay in &mut self.overlays {
+            overlay.update(now, &mut self.ctx);
+        }
+        let progress = self.transition.as_ref().and_then(|active| {
+            let elapsed = now.saturating_duration_since(active.started);
+            active.transition.progress(elapsed)
+        });
+        if progress.is_none() {
+            self.transition = None;
+        }
+        let completed = terminal.draw(|frame| {
+            frame.render_widget(Block::new().style(self.ctx.theme.base()), frame.area());
+            let [screen_area, status_area] = status_bar::layout(frame.area());
+            screen.draw(frame, screen_area, &self.ctx);
+            // The status bar and overlays stay in place on top of the transition.
+            if let (Some(active), Some(progress)) = (&self.transition, progress) {
+                let effect = active.transition.effect;
+                composite(effect, &active.from, frame.buffer_mut(), progress);
+            }
+            let status = StatusBar::new(screen.hints(), &self.ctx).position(current, count);
+            frame.render_widget(status, status_area);
+            for overlay in &self.overlays {
+                overlay.draw(frame, &self.ctx);
+            }
+        })?;
+        self.last_frame.area = completed.buffer.area;
+        self.last_frame
+            .content
+            .clone_from(&completed.buffer.content);
+        if let Some(format) = self.screenshot_request.take() {
+            self.screenshot = Some((format, completed.buffer.clone()));
+        }
+        self.scheduler.frame_drawn(now);
+        Ok(Action::Stay)
+    }
+
+    /// Replaces the carousel settings after checking that the playlist only names
+    /// existing screens.
+    pub fn set_carousel(&mut self, config: CarouselConfig) -> Result<(), PlaylistError> {
+        validate_playlist(&config.playlist, &self.screen_names())?;
+        self.ctx.carousel.config = config;
+        Ok(())
+    }
+
+    /// Keeps a copy of the next frame that is drawn, see [`App::take_screenshot`].
+    pub fn request_screenshot(&mut self, format: ScreenshotFormat) {
+        self.screenshot_request = Some(format);
+    }
+
+    /// The frame captured after [`App::request_screenshot`], once it was drawn.
+    pub fn take_screenshot(&mut self) -> Option<(ScreenshotFormat, Buffer)> {
+        self.screenshot.take()
+    }
+
+    /// Makes the screen at `index` the active one, with the transition for jumps.
+    pub fn show(&mut self, index: usize) {
+        self.switch_to(index, self.ctx.transitions.jump);
+    }
+
+    fn switch_to(&mut self, index: usize, transition: Transition) {
+        assert!(index < self.screens.len(), "no screen at index {index}");
+        self.transition = (transition.effect != Effect::Cut).then(|| ActiveTransition {
+            transition,
+            from: self.last_frame.clone(),
+            started: Instant::now(),
+        });
+        self.current = index;
+        self.screens[self.current].init(&mut self.ctx);
+        self.scheduler.reset(Instant::now());
+    }
+}
@@ -0,0 +1,200 @@
+/// Tick steps are one of these times a power of ten.
+const NICE_MANTISSAS: [f64; 4] = [1.0, 2.0, 2.5, 5.0];
+
+/// Axis bounds that start and end on evenly spaced, round ticks.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AxisScale {
+    pub bounds: [f64; 2],
+    pub step: f64,
+}
+
+impl AxisScale {
+    /// Fits `values` plus `headroom` (a fraction of their range on each side)
+    /// with at most `max_ticks` ticks. Falls back to `[-1, 1]` without values.
+    pub fn auto(values: impl IntoIterator<Item = f64>, headroom: f64, max_ticks: usize) -> Self {
+        let Some((low, high)) = values.into_iter().filter(|value| value.is_finite()).fold(
+            None,
+            |bounds: Option<(f64, f64)>, value| match bounds {
+                Some((low, high)) => Some((low.min(value), high.max(value))),
+                None => Some((value, value)),
+            },
+        ) else {
+            return Self::nice(-1.0, 1.0, max_ticks);
+        };
+        let padding = if high > low {
+            (high - low) * headroom
+        } else {
+            // A flat line still needs some room around it.
+            (low.abs() * 0.1).max(1e-3)
+        };
+        Self::nice(low - padding, high + padding, max_ticks)
+    }
+
+    /// Widens `[low, high]` to the tightest round ticks, at most `max_ticks` of them.
+    pub fn nice(low: f64, high: f64, max_ticks: usize) -> Self {
+        let max_ticks = max_ticks.max(2);
+        let range = (high - low).max(f64::EPSILON);
+        let magnitude = 10f64.powf((range / (max_ticks - 1) as f64).log10().floor());
+
+        let candidates = [magnitude, magnitude * 10.0]
+            .into_iter()
+            .flat_map(|magnitude| NICE_MANTISSAS.map(|mantissa| mantissa * magnitude));
+        let mut best: Option<AxisScale> = None;
+        for step in candidates {
+            let scale = AxisScale {
+                bounds: [(low / step).floor() * step, (high / step).ceil() * step],
+                step,
+            };
+            let tighter = best.as_ref().is_none_or(|best| scale.span() < best.span());
+            if scale.ticks().len() <= max_ticks && tighter {
+                best = Some(scale);
+            }
+        }
+        best.expect("a step of ten times the magnitude always fits")
+    }
+
+    fn span(&self) -> f64 {
+        self.bounds[1] - self.bounds[0]
+    }
+
+    /// Tick positions from the lower to the upper bound.
+    pub fn ticks(&self) -> Vec<f64> {
+        let count = (self.span() / self.step).round() as usize + 1;
+        (0..count)
+            .map(|i| self.bounds[0] + i as f64 * self.step)
+            .collect()
+    }
+
+    /// Tick labels formatted for the range, see [`format_tick`].
+    pub fn labels(&self, unit: &str) -> Vec<String> {
+        let largest = self.bounds[0].abs().max(self.bounds[1].abs());
+        self.ticks()
+            .into_iter()
+            .map(|tick| format_tick(tick, self.step, largest, unit))
+            .collect()
+    }
+}
+
+/// Evenly spaced labels for fixed bounds, like a scrolling time window.
+pub fn even_labels(bounds: [f64; 2], count: usize, unit: &str) -> Vec<String> {
+    let count = count.max(2);
+    let step = (bounds[1] - bounds[0]) / (count - 1) as f64;
+    let largest = bounds[0].abs().max(bounds[1].abs());
+    let values: Vec<f64> = (0..count).map(|i| bounds[0] + i as f64 * step).collect();
+    let mut format = TickFormat::new(step, largest, unit);
+    // The bounds needn't be round, so add decimals until every label is exact.
+    while format.decimals < 2 && values.iter().any(|&value| !format.is_exact(value)) {
+        format.decimals += 1;
+    }
+    values
+        .into_iter()
+        .map(|value| format.format(value, unit))
+        .collect()
+}
+
+/// Formats `value` with just enough decimals to tell ticks `step` apart.
+///
+/// With a unit, values use an SI prefix picked from the `largest` value on the
+/// axis (`1.5kV`, `250mV`); without one, only thousands and millions are shortened.
+pub fn format_tick(value: f64, step: f64, largest: f64, unit: &str) -> String {
+    TickFormat::new(step, largest, unit).format(value, unit)
+}
+
+struct TickFormat {
+    scale: f64,
+    prefix: &'static str,
+    decimals: usize,
+}
+
+impl TickFormat {
+    fn new(step: f64, largest: f64, unit: &str) -> Self {
+        let exponent = if largest > 0.0 {
+            (largest.log10() / 3.0).floor() as i32 * 3
+        } else {
+            0
+        };
+        let min_exponent = if unit.is_empty() { 0 } else { -6 };
+        let exponent = exponent.clamp(min_exponent, 6);
+        let prefix = match exponent {
+            -6 => "µ",
+            -3 => "m",
+            3 => "k",
+            6 => "M",
+            _ => "",
+        };
+        let scale = 10f64.powi(exponent);
+        let decimals = (-(step / scale).log10().floor()).clamp(0.0, 3.0) as usize;
+        Self {
+            scale,
+            prefix,
+            decimals,
+        }
+    }
+
+    fn rounded(&self, value: f64) -> f64 {
+        let factor = 10f64.powi(self.decimals as i32);
+        let rounded = (value / self.scale * factor).round() / factor;
+        // Avoids printing -0.
+        if rounded == 0.0 { 0.0 } else { rounded }
+    }
+
+    fn is_exact(&self, value: f64) -> bool {
+        (self.rounded(value) - value / self.scale).abs() < 1e-9
+    }
+
+    fn format(&self, value: f64, unit: &str) -> String {
+        let decimals = self.decimals;
+        format!("{:.decimals$}{}{unit}", self.rounded(value), self.prefix)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_tight_round_bounds() {
+        let scale = AxisScale::nice(-21.6, 21.6, 5);
+        assert_eq!(scale.bounds, [-25.0, 25.0]);
+        assert_eq!(scale.ticks(), [-25.0, 0.0, 25.0]);
+
+        let scale = AxisScale::nice(3.71, 4.05, 5);
+        assert_eq!(scale.step, 0.1);
+        assert_eq!(scale.ticks().len(), 5);
+        assert!((scale.bounds[0] - 3.7).abs() < 1e-9 && (scale.bounds[1] - 4.1).abs() < 1e-9);
+    }
+
+    #[test]
+    fn adds_headroom() {
+        assert_eq!(AxisScale::auto([10.0, 90.0], 0.1, 5).bounds, [0.0, 100.0]);
+        assert_eq!(AxisScale::auto([0.0, 80.0], 0.0, 5).bounds, [0.0, 80.0]);
+        assert!(AxisScale::auto([0.0, 80.0], 0.1, 5).bounds[0] < 0.0);
+    }
+
+    #[test]
+    fn handles_flat_and_missing_data() {
+        let flat = AxisScale::auto([3.9, 3.9], 0.1, 5);
+        assert!(flat.bounds[0] < 3.9 && flat.bounds[1] > 3.9);
+        assert_eq!(AxisScale::auto([], 0.1, 5).bounds, [-1.0, 1.0]);
+        assert_eq!(AxisScale::auto([f64::NAN], 0.1, 5).bounds, [-1.0, 1.0]);
+    }
+
+    #[test]
+    fn labels_share_decimals() {
+        assert_eq!(even_labels([0.0, 20.0], 3, ""), ["0", "10", "20"]);
+        assert_eq!(even_labels([2.5, 12.5], 3, "s"), ["2.5s", "7.5s", "12.5s"]);
+        assert_eq!(
+            AxisScale::nice(3.71, 4.05, 5).labels("V"),
+            ["3.7V", "3.8V", "3.9V", "4.0V", "4.1V"]
+        );
+    }
+
+    #[test]
+    fn labels_use_si_prefixes_with_unit() {
+        assert_eq!(format_tick(0.25, 0.05, 0.3, "V"), "250mV");
+        assert_eq!(format_tick(1500.0, 500.0, 2000.0, "Hz"), "1.5kHz");
+        assert_eq!(format_tick(2_000_000.0, 1e6, 2e6, ""), "2M");
+        assert_eq!(format_tick(0.25, 0.05, 0.3, ""), "0.25");
+        assert_eq!(format_tick(-0.0, 1.0, 5.0, ""), "0");
+    }
+}
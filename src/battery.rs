@@ -0,0 +1,316 @@
+mod history;
+
+pub use history::BatteryHistory;
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Battery voltage (mV) to state of charge (%) points, sorted by voltage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DischargeCurve(pub &'static [(u16, u8)]);
+
+/// Typical single-cell Li-ion curve under a light load.
+pub const LI_ION: DischargeCurve = DischargeCurve(&[
+    (3300, 0),
+    (3450, 5),
+    (3600, 15),
+    (3700, 35),
+    (3750, 50),
+    (3800, 60),
+    (3850, 70),
+    (3900, 78),
+    (4000, 88),
+    (4100, 95),
+    (4200, 100),
+]);
+
+impl DischargeCurve {
+    /// Linearly interpolates the state of charge, clamped to the ends of the curve.
+    pub fn state_of_charge(&self, millivolts: u16) -> u8 {
+        let points = self.0;
+        let (Some(&(low_mv, low_pct)), Some(&(high_mv, high_pct))) =
+            (points.first(), points.last())
+        else {
+            return 0;
+        };
+        if millivolts <= low_mv {
+            return low_pct;
+        }
+        if millivolts >= high_mv {
+            return high_pct;
+        }
+        let upper = points.iter().position(|&(mv, _)| mv >= millivolts).unwrap();
+        let (mv0, pct0) = points[upper - 1];
+        let (mv1, pct1) = points[upper];
+        let t = f32::from(millivolts - mv0) / f32::from(mv1 - mv0);
+        (f32::from(pct0) + t * (f32::from(pct1) - f32::from(pct0))).round() as u8
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChargeState {
+    Unknown,
+    Charging,
+    Discharging,
+}
+
+/// Tuning of the [`BatteryEstimator`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BatteryConfig {
+    /// Number of raw samples averaged into one reading.
+    pub window: usize,
+    /// Samples further than this from the median of the window are ignored.
+    pub outlier_millivolts: u16,
+    pub curve: DischargeCurve,
+    /// How often the filtered voltage is recorded for trend detection.
+    pub trend_interval: Duration,
+    /// Time span over which the voltage trend is measured.
+    pub trend_window: Duration,
+    /// Voltage change over the trend window that counts as charging or discharging.
+    pub trend_millivolts: u16,
+    /// Readings above this can only come from USB power, never from the cell itself.
+    pub external_power_millivolts: u16,
+}
+
+impl Default for BatteryConfig {
+    fn default() -> Self {
+        Self {
+            window: 64,
+            outlier_millivolts: 100,
+            curve: LI_ION,
+            trend_interval: Duration::from_secs(5),
+            trend_window: Duration::from_secs(60),
+            trend_millivolts: 15,
+            external_power_millivolts: 4300,
+        }
+    }
+}
+
+/// A filtered battery reading.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatteryState {
+    pub millivolts: u16,
+    pub percent: u8,
+    pub charge: ChargeState,
+}
+
+/// Turns noisy raw battery samples into a stable voltage, state of charge and charge state.
+#[derive(Debug, Clone)]
+pub struct BatteryEstimator {
+    config: BatteryConfig,
+    samples: VecDeque<u16>,
+    trend: VecDeque<(Instant, u16)>,
+    charge: ChargeState,
+}
+
+impl BatteryEstimator {
+    pub fn new(config: BatteryConfig) -> Self {
+        Self {
+            config,
+            samples: VecDeque::with_capacity(config.window),
+            trend: VecDeque::new(),
+            charge: ChargeState::Unknown,
+        }
+    }
+
+    /// Adds a raw battery voltage sample taken at `now`.
+    pub fn push(&mut self, millivolts: u16, now: Instant) {
+        if self.samples.len() == self.config.window {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(millivolts);
+        self.update_trend(now);
+    }
+
+    pub fn curve(&self) -> &DischargeCurve {
+        &self.config.curve
+    }
+
+    /// Forgets all samples, e.g. after the calibration changed.
+    pub fn clear(&mut self) {
+        self.samples.clear();
+        self.trend.clear();
+        self.charge = ChargeState::Unknown;
+    }
+
+    /// Average of the current window without outliers, `None` before the first sample.
+    pub fn millivolts(&self) -> Option<u16> {
+        let mut sorted: Vec<u16> = self.samples.iter().copied().collect();
+        sorted.sort_unstable();
+        let median = *sorted.get(sorted.len() / 2)?;
+        let inliers: Vec<u32> = sorted
+            .into_iter()
+            .filter(|sample| sample.abs_diff(median) <= self.config.outlier_millivolts)
+            .map(u32::from)
+            .collect();
+        Some((inliers.iter().sum::<u32>() / inliers.len() as u32) as u16)
+    }
+
+    pub fn state(&self) -> Option<BatteryState> {
+        let millivolts = self.millivolts()?;
+        Some(BatteryState {
+            millivolts,
+            percent: self.config.curve.state_of_charge(millivolts),
+            charge: self.charge,
+        })
+    }
+
+    fn update_trend(&mut self, now: Instant) {
+        let Some(millivolts) = self.millivolts() else {
+            return;
+        };
+        if millivolts >= self.config.external_power_millivolts {
+            self.charge = ChargeState::Charging;
+        }
+        let recorded_recently = self
+            .trend
+            .back()
+            .is_some_and(|&(at, _)| now.duration_since(at) < self.config.trend_interval);
+        if recorded_recently {
+            return;
+        }
+        self.trend.push_back((now, millivolts));
+        while self
+            .trend
+            .front()
+            .is_some_and(|&(at, _)| now.duration_since(at) > self.config.trend_window)
+        {
+            self.trend.pop_front();
+        }
+
+        let &(since, start) = self.trend.front().unwrap();
+        // Wait for at least half a window of history before judging the trend.
+        if now.duration_since(since) < self.config.trend_window / 2 {
+            return;
+        }
+        let rise = i32::from(millivolts) - i32::from(start);
+        let threshold = i32::from(self.config.trend_millivolts);
+        if millivolts >= self.config.external_power_millivolts || rise >= threshold {
+            self.charge = ChargeState::Charging;
+        } else if rise <= -threshold {
+            self.charge = ChargeState::Discharging;
+        }
+    }
+}
+
+impl Default for BatteryEstimator {
+    fn default() -> Self {
+        Self::new(BatteryConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Raw readings of a TTGO T-Display at rest, one per frame, including the
+    /// occasional ADC glitch.
+    const RESTING: &[u16] = &[
+        3912, 3906, 3918, 3902, 3910, 0, 3914, 3908, 3904, 3916, 3911, 3907, 6142, 3913, 3905,
+        3909, 3915, 3903, 3912, 3908, 3910, 3917, 3906, 2980, 3911, 3909, 3904, 3914,
+    ];
+
+    /// Feeds `samples` one per `interval` and returns the final state.
+    fn replay(
+        estimator: &mut BatteryEstimator,
+        samples: impl IntoIterator<Item = u16>,
+        start: Instant,
+        interval: Duration,
+    ) -> Option<BatteryState> {
+        for (i, sample) in samples.into_iter().enumerate() {
+            estimator.push(sample, start + interval * i as u32);
+        }
+        estimator.state()
+    }
+
+    /// A voltage ramp from `from` to `to` over two minutes, sampled every second, with
+    /// the small jitter seen on the real ADC.
+    fn ramp(from: u16, to: u16) -> impl Iterator<Item = u16> {
+        const JITTER: [i32; 5] = [4, -6, 2, -3, 5];
+        (0..120).map(move |i| {
+            let value = i32::from(from) + (i32::from(to) - i32::from(from)) * i / 120;
+            (value + JITTER[i as usize % JITTER.len()]) as u16
+        })
+    }
+
+    #[test]
+    fn no_samples_no_state() {
+        assert_eq!(BatteryEstimator::default().state(), None);
+    }
+
+    #[test]
+    fn rejects_adc_glitches() {
+        let mut estimator = BatteryEstimator::default();
+        let state = replay(
+            &mut estimator,
+            RESTING.iter().copied(),
+            Instant::now(),
+            Duration::from_millis(33),
+        )
+        .unwrap();
+        assert!((3905..=3913).contains(&state.millivolts), "{state:?}");
+        assert_eq!(state.percent, 79);
+        assert_eq!(state.charge, ChargeState::Unknown);
+    }
+
+    #[test]
+    fn curve_interpolates_and_clamps() {
+        assert_eq!(LI_ION.state_of_charge(3000), 0);
+        assert_eq!(LI_ION.state_of_charge(3300), 0);
+        assert_eq!(LI_ION.state_of_charge(3725), 43);
+        assert_eq!(LI_ION.state_of_charge(4150), 98);
+        assert_eq!(LI_ION.state_of_charge(4500), 100);
+        assert_eq!(DischargeCurve(&[]).state_of_charge(3700), 0);
+    }
+
+    #[test]
+    fn detects_charging_from_rising_voltage() {
+        let mut estimator = BatteryEstimator::default();
+        let state = replay(
+            &mut estimator,
+            ramp(3800, 3900),
+            Instant::now(),
+            Duration::from_secs(1),
+        );
+        assert_eq!(state.unwrap().charge, ChargeState::Charging);
+    }
+
+    #[test]
+    fn detects_discharging_from_falling_voltage() {
+        let mut estimator = BatteryEstimator::default();
+        let state = replay(
+            &mut estimator,
+            ramp(3900, 3820),
+            Instant::now(),
+            Duration::from_secs(1),
+        );
+        assert_eq!(state.unwrap().charge, ChargeState::Discharging);
+    }
+
+    #[test]
+    fn steady_voltage_keeps_charge_state() {
+        let mut estimator = BatteryEstimator::default();
+        let start = Instant::now();
+        replay(
+            &mut estimator,
+            ramp(3900, 3820),
+            start,
+            Duration::from_secs(1),
+        );
+        let later = start + Duration::from_secs(120);
+        let state = replay(
+            &mut estimator,
+            ramp(3820, 3822),
+            later,
+            Duration::from_secs(1),
+        );
+        assert_eq!(state.unwrap().charge, ChargeState::Discharging);
+    }
+
+    #[test]
+    fn usb_power_counts_as_charging() {
+        let mut estimator = BatteryEstimator::default();
+        estimator.push(4720, Instant::now());
+        assert_eq!(estimator.state().unwrap().charge, ChargeState::Charging);
+    }
+}
@@ -0,0 +1,207 @@
+use super::DischargeCurve;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Fewest samples needed before estimating the time remaining.
+const MIN_ESTIMATE_SAMPLES: usize = 8;
+
+/// Fixed-size ring buffer of battery voltages taken at a regular interval.
+#[derive(Debug, Clone)]
+pub struct BatteryHistory {
+    interval: Duration,
+    capacity: usize,
+    samples: VecDeque<(Instant, u16)>,
+}
+
+impl BatteryHistory {
+    pub fn new(interval: Duration, capacity: usize) -> Self {
+        assert!(
+            capacity > 0,
+            "BatteryHistory needs room for at least one sample"
+        );
+        Self {
+            interval,
+            capacity,
+            samples: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Time span covered by a full buffer.
+    pub fn span(&self) -> Duration {
+        self.interval * self.capacity as u32
+    }
+
+    /// Records `millivolts` unless the last sample is less than an interval old.
+    pub fn record(&mut self, millivolts: u16, now: Instant) {
+        let due = self
+            .samples
+            .back()
+            .is_none_or(|&(at, _)| now.duration_since(at) >= self.interval);
+        if !due {
+            return;
+        }
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back((now, millivolts));
+    }
+
+    /// Samples from oldest to newest.
+    pub fn samples(&self) -> impl Iterator<Item = (Instant, u16)> + '_ {
+        self.samples.iter().copied()
+    }
+
+    pub fn latest(&self) -> Option<(Instant, u16)> {
+        self.samples.back().copied()
+    }
+
+    /// Samples at most `span` older than the latest one, from oldest to newest.
+    pub fn recent(&self, span: Duration) -> impl Iterator<Item = (Instant, u16)> + '_ {
+        let latest = self.latest().map(|(at, _)| at);
+        self.samples()
+            .filter(move |&(at, _)| latest.is_some_and(|latest| latest.duration_since(at) <= span))
+    }
+
+    pub fn min(&self, span: Duration) -> Option<u16> {
+        self.recent(span).map(|(_, mv)| mv).min()
+    }
+
+    pub fn max(&self, span: Duration) -> Option<u16> {
+        self.recent(span).map(|(_, mv)| mv).max()
+    }
+
+    pub fn average(&self, span: Duration) -> Option<u16> {
+        let (sum, count) = self.recent(span).fold((0, 0), |(sum, count), (_, mv)| {
+            (sum + u32::from(mv), count + 1)
+        });
+        (count > 0).then(|| (sum / count) as u16)
+    }
+
+    /// Time until the state of charge reaches 0% at the discharge rate seen in the
+    /// last `span`, `None` while not discharging or without enough samples.
+    pub fn time_remaining(&self, curve: &DischargeCurve, span: Duration) -> Option<Duration> {
+        let samples: Vec<(Instant, u16)> = self.recent(span).collect();
+        if samples.len() < MIN_ESTIMATE_SAMPLES {
+            return None;
+        }
+        // Least squares slope of the state of charge over time.
+        let (start, _) = samples[0];
+        let points: Vec<(f32, f32)> = samples
+            .iter()
+            .map(|&(at, mv)| {
+                let seconds = at.duration_since(start).as_secs_f32();
+                (seconds, f32::from(curve.state_of_charge(mv)))
+            })
+            .collect();
+        let count = points.len() as f32;
+        let mean_t = points.iter().map(|&(t, _)| t).sum::<f32>() / count;
+        let mean_p = points.iter().map(|&(_, p)| p).sum::<f32>() / count;
+        let covariance: f32 = points
+            .iter()
+            .map(|&(t, p)| (t - mean_t) * (p - mean_p))
+            .sum();
+        let variance: f32 = points.iter().map(|&(t, _)| (t - mean_t).powi(2)).sum();
+        let slope = covariance / variance;
+        if !slope.is_finite() || slope >= 0.0 {
+            return None;
+        }
+        let (_, latest) = self.latest()?;
+        let percent = f32::from(curve.state_of_charge(latest));
+        Some(Duration::from_secs_f32(percent / -slope))
+    }
+}
+
+impl Default for BatteryHistory {
+    /// Two hours of history, one sample every 15 seconds.
+    fn default() -> Self {
+        Self::new(Duration::from_secs(15), 480)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::LI_ION;
+    use super::*;
+
+    /// Filtered voltage of a TTGO T-Display running the demo, one reading a minute.
+    const DISCHARGE: &[u16] = &[
+        4012, 4007, 4003, 3998, 3996, 3991, 3987, 3984, 3979, 3976, 3971, 3968, 3964, 3960, 3957,
+        3952, 3949, 3945, 3941, 3938,
+    ];
+
+    fn record_every_minute(history: &mut BatteryHistory, samples: &[u16]) {
+        let start = Instant::now();
+        for (minute, &mv) in samples.iter().enumerate() {
+            history.record(mv, start + Duration::from_secs(60) * minute as u32);
+        }
+    }
+
+    #[test]
+    fn samples_at_interval() {
+        let mut history = BatteryHistory::new(Duration::from_secs(10), 4);
+        let start = Instant::now();
+        for second in 0..25 {
+            history.record(3900 + second, start + Duration::from_secs(second.into()));
+        }
+        let samples: Vec<u16> = history.samples().map(|(_, mv)| mv).collect();
+        assert_eq!(samples, [3900, 3910, 3920]);
+    }
+
+    #[test]
+    fn drops_oldest_when_full() {
+        let mut history = BatteryHistory::new(Duration::from_secs(60), 5);
+        record_every_minute(&mut history, DISCHARGE);
+        let samples: Vec<u16> = history.samples().map(|(_, mv)| mv).collect();
+        assert_eq!(samples, DISCHARGE[DISCHARGE.len() - 5..]);
+        assert_eq!(history.span(), Duration::from_secs(300));
+    }
+
+    #[test]
+    fn statistics() {
+        let mut history = BatteryHistory::default();
+        let span = history.span();
+        assert_eq!(history.average(span), None);
+        record_every_minute(&mut history, DISCHARGE);
+        assert_eq!(history.min(span), Some(3938));
+        assert_eq!(history.max(span), Some(4012));
+        assert_eq!(history.average(span), Some(3973));
+    }
+
+    #[test]
+    fn statistics_of_the_last_minutes() {
+        let mut history = BatteryHistory::default();
+        record_every_minute(&mut history, DISCHARGE);
+        let span = Duration::from_secs(4 * 60);
+        assert_eq!(history.recent(span).count(), 5);
+        assert_eq!(history.min(span), Some(3938));
+        assert_eq!(history.max(span), Some(3952));
+        assert_eq!(history.average(span), Some(3945));
+        assert_eq!(history.time_remaining(&LI_ION, span), None);
+    }
+
+    #[test]
+    fn estimates_time_remaining_while_discharging() {
+        let mut history = BatteryHistory::default();
+        record_every_minute(&mut history, &DISCHARGE[..4]);
+        assert_eq!(history.time_remaining(&LI_ION, history.span()), None);
+
+        let mut history = BatteryHistory::default();
+        record_every_minute(&mut history, DISCHARGE);
+        let remaining = history.time_remaining(&LI_ION, history.span()).unwrap();
+        // About 7% used in 19 minutes with 82% left.
+        let minutes = remaining.as_secs() / 60;
+        assert!((200..260).contains(&minutes), "{minutes} minutes");
+    }
+
+    #[test]
+    fn no_estimate_while_charging() {
+        let mut history = BatteryHistory::default();
+        let charging: Vec<u16> = DISCHARGE.iter().rev().copied().collect();
+        record_every_minute(&mut history, &charging);
+        assert_eq!(history.time_remaining(&LI_ION, history.span()), None);
+    }
+}
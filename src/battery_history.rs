@@ -0,0 +1,140 @@
+use crate::axis::AxisScale;
+use crate::chart::bold_ends;
+use crate::input::InputEvent;
+use crate::screen::{Action, Context, Screen};
+use mousefood::prelude::*;
+use mousefood::ratatui::widgets::{Axis, Block, Chart, Dataset, GraphType};
+use std::time::Duration;
+
+/// Time spans the chart cycles through, capped at what the history holds.
+const SPANS: [Duration; 3] = [
+    Duration::from_secs(10 * 60),
+    Duration::from_secs(30 * 60),
+    Duration::from_secs(2 * 60 * 60),
+];
+const Y_TICKS: usize = 5;
+
+/// Plots the battery voltage history with its statistics and time remaining.
+#[derive(Debug, Default)]
+pub struct BatteryHistoryApp {
+    span: usize,
+}
+
+impl BatteryHistoryApp {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Screen for BatteryHistoryApp {
+    fn name(&self) -> &'static str {
+        "history"
+    }
+
+    fn handle_event(&mut self, event: InputEvent, _ctx: &mut Context) -> Action {
+        match event {
+            InputEvent::ShortPress => {
+                self.span = (self.span + 1) % SPANS.len();
+                Action::Stay
+            }
+            InputEvent::LongPress => Action::NextScreen,
+            InputEvent::DoublePress => Action::PreviousScreen,
+            InputEvent::Repeat => Action::Stay,
+        }
+    }
+
+    fn hints(&self) -> &'static str {
+        "[S1] zoom  [hold] next"
+    }
+
+    fn draw(&self, frame: &mut Frame, area: Rect, ctx: &Context) {
+        let theme = &ctx.theme;
+        let history = &ctx.battery_history;
+        let span = SPANS[self.span].min(history.span());
+
+        // Minutes before the latest sample against volts.
+        let data: Vec<(f64, f64)> = match history.latest() {
+            Some((latest, _)) => history
+                .recent(span)
+                .map(|(at, mv)| {
+                    let ago = latest.duration_since(at);
+                    (-ago.as_secs_f64() / 60.0, f64::from(mv) / 1000.0)
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+        let y_scale = if data.is_empty() {
+            AxisScale::nice(3.3, 4.2, Y_TICKS)
+        } else {
+            AxisScale::auto(data.iter().map(|&(_, volts)| volts), 0.1, Y_TICKS)
+        };
+
+        let span_minutes = span.as_secs_f64() / 60.0;
+        let x_labels = bold_ends(vec![format_ago(span), format_ago(span / 2), "now".into()]);
+        let y_labels = bold_ends(y_scale.labels("V"));
+        let datasets = vec![
+            Dataset::default()
+                .marker(symbols::Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(theme.secondary))
+                .data(&data),
+        ];
+        let chart = Chart::new(datasets)
+            .block(
+                Block::bordered()
+                    .border_style(theme.border)
+                    .title(format!("Battery, last {}", format_span(span))),
+            )
+            .x_axis(
+                Axis::default()
+                    .style(Style::default().fg(theme.footer))
+                    .labels(x_labels)
+                    .bounds([-span_minutes, 0.0]),
+            )
+            .y_axis(
+                Axis::default()
+                    .style(Style::default().fg(theme.footer))
+                    .labels(y_labels)
+                    .bounds(y_scale.bounds),
+            );
+
+        let [stats_area, chart_area] =
+            Layout::vertical([Constraint::Length(1), Constraint::Min(1)]).areas(area);
+
+        let volts = |mv: Option<u16>| match mv {
+            Some(mv) => format!("{:.2}", f32::from(mv) / 1000.0),
+            None => "-.--".to_string(),
+        };
+        // Statistics of the plotted span only.
+        let remaining = match history.time_remaining(ctx.battery.curve(), span) {
+            Some(remaining) => format!("~{}", format_span(remaining)),
+            None => "--".to_string(),
+        };
+        let stats = Line::from(vec![
+            "min ".fg(theme.footer),
+            volts(history.min(span)).into(),
+            " avg ".fg(theme.footer),
+            volts(history.average(span)).into(),
+            " max ".fg(theme.footer),
+            volts(history.max(span)).into(),
+            "  ".into(),
+            remaining.fg(theme.warning),
+        ]);
+        frame.render_widget(stats.centered(), stats_area);
+        frame.render_widget(chart, chart_area);
+    }
+}
+
+/// Formats a duration as hours and minutes, e.g. `2h`, `1h30m` or `10m`.
+fn format_span(duration: Duration) -> String {
+    let minutes = duration.as_secs() / 60;
+    match (minutes / 60, minutes % 60) {
+        (0, minutes) => format!("{minutes}m"),
+        (hours, 0) => format!("{hours}h"),
+        (hours, minutes) => format!("{hours}h{minutes}m"),
+    }
+}
+
+fn format_ago(duration: Duration) -> String {
+    format!("-{}", format_span(duration))
+}
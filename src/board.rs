@@ -0,0 +1,363 @@
+use mipidsi::options::{ColorInversion, Rotation};
+use std::fmt;
+
+/// Board used when `DEMO_BOARD` isn't set at build time.
+const DEFAULT_BOARD: &str = "ttgo-t-display";
+
+/// Display controllers supported by the firmware.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PanelModel {
+    St7789,
+    Ili9342c,
+}
+
+impl PanelModel {
+    /// Size of the controller's frame memory, in its native orientation.
+    pub const fn frame_memory(self) -> (u16, u16) {
+        match self {
+            PanelModel::St7789 => (240, 320),
+            PanelModel::Ili9342c => (320, 240),
+        }
+    }
+}
+
+/// GPIO numbers of everything the demo is wired to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoardPins {
+    pub backlight: u8,
+    pub sclk: u8,
+    pub mosi: u8,
+    pub cs: u8,
+    pub dc: u8,
+    pub reset: u8,
+    /// Active-low push button used as S1.
+    pub button: u8,
+    /// Second active-low button, held during a long press of S1 to take a screenshot.
+    pub chord_button: Option<u8>,
+}
+
+/// Fully charged single-cell Li-ion voltage the ADC range has to cover.
+const FULL_BATTERY_MILLIVOLTS: f32 = 4200.0;
+
+/// ADC input attenuation, which sets the voltage range measurable at the pin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdcAttenuation {
+    Db0,
+    Db2_5,
+    Db6,
+    Db11,
+}
+
+impl AdcAttenuation {
+    /// Highest pin voltage the ESP32 ADC measures accurately with this attenuation.
+    pub const fn max_millivolts(self) -> u16 {
+        match self {
+            AdcAttenuation::Db0 => 950,
+            AdcAttenuation::Db2_5 => 1250,
+            AdcAttenuation::Db6 => 1750,
+            AdcAttenuation::Db11 => 2450,
+        }
+    }
+}
+
+/// Battery voltage sensing through a resistor divider into ADC1.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BatterySense {
+    pub adc_pin: u8,
+    /// Nominal battery voltage divided by the voltage at the ADC pin, refined per
+    /// device by [`Calibration`](crate::calibration::Calibration).
+    pub divider: f32,
+    pub attenuation: AdcAttenuation,
+}
+
+/// Everything that differs between the ESP32 boards the demo runs on.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoardProfile {
+    pub name: &'static str,
+    pub pins: BoardPins,
+    pub panel: PanelModel,
+    /// Offset of the visible area in the controller's frame memory.
+    pub offset: (u16, u16),
+    /// Visible panel size before rotation.
+    pub size: (u16, u16),
+    pub rotation: Rotation,
+    pub inversion: ColorInversion,
+    pub battery: Option<BatterySense>,
+}
+
+/// LILYGO TTGO T-Display with its 1.14" 135x240 ST7789 panel.
+pub const TTGO_T_DISPLAY: BoardProfile = BoardProfile {
+    name: "ttgo-t-display",
+    pins: BoardPins {
+        backlight: 4,
+        sclk: 18,
+        mosi: 19,
+        cs: 5,
+        dc: 16,
+        reset: 23,
+        button: 0,
+        chord_button: Some(35),
+    },
+    panel: PanelModel::St7789,
+    offset: (52, 40),
+    size: (135, 240),
+    rotation: Rotation::Deg90,
+    inversion: ColorInversion::Inverted,
+    battery: Some(BatterySense {
+        adc_pin: 34,
+        divider: 2.0,
+        attenuation: AdcAttenuation::Db11,
+    }),
+};
+
+/// M5Stack Basic with its 320x240 ILI9342C panel, button A as S1 and button B for chords.
+///
+/// The battery is managed by an IP5306 over I2C, so there is no ADC reading.
+pub const M5STACK_BASIC: BoardProfile = BoardProfile {
+    name: "m5stack-basic",
+    pins: BoardPins {
+        backlight: 32,
+        sclk: 18,
+        mosi: 23,
+        cs: 14,
+        dc: 27,
+        reset: 33,
+        button: 39,
+        chord_button: Some(38),
+    },
+    panel: PanelModel::Ili9342c,
+    offset: (0, 0),
+    size: (320, 240),
+    rotation: Rotation::Deg0,
+    inversion: ColorInversion::Inverted,
+    battery: None,
+};
+
+pub const BOARDS: &[BoardProfile] = &[TTGO_T_DISPLAY, M5STACK_BASIC];
+
+/// Why a [`BoardProfile`] can't work on an ESP32.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoardError {
+    NoSuchPin(u8),
+    /// GPIO6-11 are wired to the SPI flash.
+    FlashPin(u8),
+    /// GPIO34-39 can't drive outputs.
+    InputOnlyPin(u8),
+    DuplicatePin(u8),
+    /// Battery sensing needs an ADC1 pin, ADC2 is unusable while Wi-Fi is on.
+    NotAdc1Pin(u8),
+    /// Offset plus size doesn't fit in the controller's frame memory.
+    DisplayOutOfRange,
+    /// A divider below 1 would mean the ADC sees more than the battery voltage.
+    InvalidDivider,
+    /// A full battery would exceed the ADC range with this divider and attenuation.
+    AdcRangeExceeded,
+}
+
+impl fmt::Display for BoardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BoardError::NoSuchPin(pin) => write!(f, "GPIO{pin} doesn't exist on the ESP32"),
+            BoardError::FlashPin(pin) => write!(f, "GPIO{pin} is reserved for the SPI flash"),
+            BoardError::InputOnlyPin(pin) => write!(f, "GPIO{pin} is input-only"),
+            BoardError::DuplicatePin(pin) => write!(f, "GPIO{pin} is assigned more than once"),
+            BoardError::NotAdc1Pin(pin) => write!(f, "GPIO{pin} is not an ADC1 pin"),
+            BoardError::DisplayOutOfRange => {
+                write!(f, "display offset and size exceed the frame memory")
+            }
+            BoardError::InvalidDivider => write!(f, "battery divider must be at least 1"),
+            BoardError::AdcRangeExceeded => {
+                write!(f, "a full battery exceeds the ADC range at the divider")
+            }
+        }
+    }
+}
+
+impl std::error::Error for BoardError {}
+
+impl BoardProfile {
+    /// The profile picked by the `DEMO_BOARD` build-time variable (see `.cargo/config.toml`).
+    pub fn selected() -> &'static BoardProfile {
+        let name = option_env!("DEMO_BOARD").unwrap_or(DEFAULT_BOARD);
+        Self::by_name(name).unwrap_or_else(|| panic!("unknown DEMO_BOARD {name:?}"))
+    }
+
+    pub fn by_name(name: &str) -> Option<&'static BoardProfile> {
+        BOARDS.iter().find(|board| board.name == name)
+    }
+
+    /// Display resolution in pixels after rotation.
+    pub fn resolution(&self) -> (u16, u16) {
+        match self.rotation {
+            Rotation::Deg0 | Rotation::Deg180 => self.size,
+            Rotation::Deg90 | Rotation::Deg270 => (self.size.1, self.size.0),
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), BoardError> {
+        let pins = self.pins;
+        let outputs = [
+            pins.backlight,
+            pins.sclk,
+            pins.mosi,
+            pins.cs,
+            pins.dc,
+            pins.reset,
+        ];
+        let inputs = [
+            Some(pins.button),
+            pins.chord_button,
+            self.battery.map(|battery| battery.adc_pin),
+        ];
+        let all = outputs.iter().chain(inputs.iter().flatten());
+
+        let mut used = Vec::new();
+        for &pin in all {
+            if !is_esp32_pin(pin) {
+                return Err(BoardError::NoSuchPin(pin));
+            }
+            if (6..=11).contains(&pin) {
+                return Err(BoardError::FlashPin(pin));
+            }
+            if used.contains(&pin) {
+                return Err(BoardError::DuplicatePin(pin));
+            }
+            used.push(pin);
+        }
+        if let Some(&pin) = outputs.iter().find(|&&pin| (34..=39).contains(&pin)) {
+            return Err(BoardError::InputOnlyPin(pin));
+        }
+
+        if let Some(battery) = self.battery {
+            if !(32..=39).contains(&battery.adc_pin) {
+                return Err(BoardError::NotAdc1Pin(battery.adc_pin));
+            }
+            if battery.divider.is_nan() || battery.divider < 1.0 {
+                return Err(BoardError::InvalidDivider);
+            }
+            let max = f32::from(battery.attenuation.max_millivolts());
+            if FULL_BATTERY_MILLIVOLTS / battery.divider > max {
+                return Err(BoardError::AdcRangeExceeded);
+            }
+        }
+
+        let (max_width, max_height) = self.panel.frame_memory();
+        let fits = |offset: u16, size: u16, max: u16| {
+            size > 0 && u32::from(offset) + u32::from(size) <= u32::from(max)
+        };
+        if !fits(self.offset.0, self.size.0, max_width)
+            || !fits(self.offset.1, self.size.1, max_height)
+        {
+            return Err(BoardError::DisplayOutOfRange);
+        }
+        Ok(())
+    }
+}
+
+fn is_esp32_pin(pin: u8) -> bool {
+    matches!(pin, 0..=19 | 21..=23 | 25..=27 | 32..=39)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn built_in_boards_are_valid() {
+        for board in BOARDS {
+            assert_eq!(board.validate(), Ok(()), "{}", board.name);
+        }
+    }
+
+    #[test]
+    fn board_names_are_unique() {
+        for board in BOARDS {
+            assert_eq!(BoardProfile::by_name(board.name), Some(board));
+        }
+    }
+
+    #[test]
+    fn default_board_exists() {
+        assert!(BoardProfile::by_name(DEFAULT_BOARD).is_some());
+    }
+
+    #[test]
+    fn resolution_follows_rotation() {
+        assert_eq!(TTGO_T_DISPLAY.resolution(), (240, 135));
+        assert_eq!(M5STACK_BASIC.resolution(), (320, 240));
+    }
+
+    #[test]
+    fn rejects_bad_pins() {
+        let with_pins = |pins: BoardPins| BoardProfile {
+            pins,
+            ..TTGO_T_DISPLAY
+        };
+        let pins = TTGO_T_DISPLAY.pins;
+        assert_eq!(
+            with_pins(BoardPins { dc: 24, ..pins }).validate(),
+            Err(BoardError::NoSuchPin(24))
+        );
+        assert_eq!(
+            with_pins(BoardPins { cs: 7, ..pins }).validate(),
+            Err(BoardError::FlashPin(7))
+        );
+        assert_eq!(
+            with_pins(BoardPins { reset: 36, ..pins }).validate(),
+            Err(BoardError::InputOnlyPin(36))
+        );
+        assert_eq!(
+            with_pins(BoardPins { mosi: 18, ..pins }).validate(),
+            Err(BoardError::DuplicatePin(18))
+        );
+        assert_eq!(
+            with_pins(BoardPins { button: 34, ..pins }).validate(),
+            Err(BoardError::DuplicatePin(34))
+        );
+        assert_eq!(
+            with_pins(BoardPins {
+                chord_button: Some(0),
+                ..pins
+            })
+            .validate(),
+            Err(BoardError::DuplicatePin(0))
+        );
+    }
+
+    #[test]
+    fn rejects_bad_battery_sense() {
+        let with_battery = |adc_pin, divider, attenuation| BoardProfile {
+            battery: Some(BatterySense {
+                adc_pin,
+                divider,
+                attenuation,
+            }),
+            ..TTGO_T_DISPLAY
+        };
+        assert_eq!(
+            with_battery(25, 2.0, AdcAttenuation::Db11).validate(),
+            Err(BoardError::NotAdc1Pin(25))
+        );
+        assert_eq!(
+            with_battery(34, 0.5, AdcAttenuation::Db11).validate(),
+            Err(BoardError::InvalidDivider)
+        );
+        assert_eq!(
+            with_battery(34, 2.0, AdcAttenuation::Db6).validate(),
+            Err(BoardError::AdcRangeExceeded)
+        );
+        assert_eq!(
+            with_battery(34, 4.5, AdcAttenuation::Db0).validate(),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn rejects_display_outside_frame_memory() {
+        let board = BoardProfile {
+            offset: (120, 40),
+            ..TTGO_T_DISPLAY
+        };
+        assert_eq!(board.validate(), Err(BoardError::DisplayOutOfRange));
+    }
+}
@@ -0,0 +1,132 @@
+use crate::board::BatterySense;
+
+/// Accepted range of the gain correction, anything outside points to a wrong reference.
+const GAIN_RANGE: std::ops::RangeInclusive<f32> = 0.5..=1.5;
+
+/// Per-device correction turning the voltage at the ADC pin into the battery voltage.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Calibration {
+    /// Battery voltage divided by the voltage at the ADC pin.
+    pub divider: f32,
+    /// Correction factor for resistor tolerances and ADC error.
+    pub gain: f32,
+    pub offset_millivolts: i16,
+}
+
+impl Calibration {
+    /// Uncalibrated reading through a divider.
+    pub const fn new(divider: f32) -> Self {
+        Self {
+            divider,
+            gain: 1.0,
+            offset_millivolts: 0,
+        }
+    }
+
+    /// Starting point for a board before it has been calibrated.
+    pub fn for_board(battery: Option<BatterySense>) -> Self {
+        battery.map_or_else(Self::default, |battery| Self::new(battery.divider))
+    }
+
+    /// Battery voltage for a reading of `adc_millivolts` at the ADC pin.
+    pub fn apply(&self, adc_millivolts: u16) -> u16 {
+        let millivolts = f32::from(adc_millivolts) * self.divider * self.gain
+            + f32::from(self.offset_millivolts);
+        millivolts.clamp(0.0, f32::from(u16::MAX)).round() as u16
+    }
+
+    /// Adjusts the gain so that a battery currently read as `measured` reads as `reference`.
+    ///
+    /// Returns `None` if the correction is implausibly large.
+    pub fn with_reference(&self, measured: u16, reference: u16) -> Option<Self> {
+        let offset = f32::from(self.offset_millivolts);
+        let measured = f32::from(measured) - offset;
+        let reference = f32::from(reference) - offset;
+        if measured <= 0.0 || reference <= 0.0 {
+            return None;
+        }
+        let gain = self.gain * reference / measured;
+        GAIN_RANGE
+            .contains(&(gain / self.gain))
+            .then_some(Self { gain, ..*self })
+    }
+
+    /// Compact representation, which is how firmware before the settings store
+    /// kept it in NVS.
+    pub fn to_bytes(&self) -> [u8; 10] {
+        let mut bytes = [0; 10];
+        bytes[0..4].copy_from_slice(&self.divider.to_le_bytes());
+        bytes[4..8].copy_from_slice(&self.gain.to_le_bytes());
+        bytes[8..10].copy_from_slice(&self.offset_millivolts.to_le_bytes());
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let bytes: &[u8; 10] = bytes.try_into().ok()?;
+        let calibration = Self {
+            divider: f32::from_le_bytes(bytes[0..4].try_into().unwrap()),
+            gain: f32::from_le_bytes(bytes[4..8].try_into().unwrap()),
+            offset_millivolts: i16::from_le_bytes(bytes[8..10].try_into().unwrap()),
+        };
+        let valid = calibration.divider >= 1.0 && calibration.gain > 0.0;
+        valid.then_some(calibration)
+    }
+}
+
+impl Default for Calibration {
+    fn default() -> Self {
+        Self::new(1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn applies_divider_gain_and_offset() {
+        let calibration = Calibration {
+            divider: 2.0,
+            gain: 1.05,
+            offset_millivolts: -30,
+        };
+        assert_eq!(calibration.apply(1900), 3960);
+        assert_eq!(Calibration::new(2.0).apply(1900), 3800);
+        assert_eq!(calibration.apply(0), 0);
+    }
+
+    #[test]
+    fn reference_corrects_gain() {
+        let calibration = Calibration {
+            divider: 2.0,
+            gain: 1.0,
+            offset_millivolts: 20,
+        };
+        let measured = calibration.apply(1900);
+        let corrected = calibration.with_reference(measured, 3900).unwrap();
+        assert_eq!(corrected.apply(1900), 3900);
+        assert_eq!(corrected.divider, 2.0);
+        assert_eq!(corrected.offset_millivolts, 20);
+    }
+
+    #[test]
+    fn rejects_implausible_reference() {
+        let calibration = Calibration::new(2.0);
+        assert_eq!(calibration.with_reference(3800, 7600), None);
+        assert_eq!(calibration.with_reference(3800, 0), None);
+        assert_eq!(calibration.with_reference(0, 3800), None);
+    }
+
+    #[test]
+    fn survives_storage() {
+        let calibration = Calibration {
+            divider: 3.2,
+            gain: 0.97,
+            offset_millivolts: -12,
+        };
+        let bytes = calibration.to_bytes();
+        assert_eq!(Calibration::from_bytes(&bytes), Some(calibration));
+        assert_eq!(Calibration::from_bytes(&bytes[..9]), None);
+        assert_eq!(Calibration::from_bytes(&[0; 10]), None);
+    }
+}
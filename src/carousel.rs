@@ -0,0 +1,303 @@
+use rand::seq::SliceRandom;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// One stop of the carousel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Slide {
+    /// [`Screen::name`](crate::screen::Screen::name) of the screen to show.
+    pub screen: String,
+    /// How long the screen is shown, [`CarouselConfig::dwell`] if not given.
+    pub dwell: Option<Duration>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CarouselConfig {
+    pub enabled: bool,
+    /// Screens in the order they are shown. Empty shows every screen in order.
+    pub playlist: Vec<Slide>,
+    /// Reorders the playlist at random every round.
+    pub shuffle: bool,
+    /// How long a slide without its own dwell time is shown.
+    pub dwell: Duration,
+    /// Idle time after a button press before the carousel moves on again.
+    pub resume_after: Duration,
+}
+
+impl Default for CarouselConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            playlist: Vec::new(),
+            shuffle: false,
+            dwell: Duration::from_secs(10),
+            resume_after: Duration::from_secs(30),
+        }
+    }
+}
+
+impl CarouselConfig {
+    /// The carousel set by the `DEMO_PLAYLIST` build-time variable (see `.cargo/config.toml`),
+    /// disabled if it is empty.
+    pub fn selected() -> Self {
+        let playlist = option_env!("DEMO_PLAYLIST").unwrap_or_default();
+        Self {
+            enabled: !playlist.trim().is_empty(),
+            playlist: parse_playlist(playlist)
+                .unwrap_or_else(|error| panic!("invalid DEMO_PLAYLIST: {error}")),
+            ..Self::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlaylistError {
+    /// The dwell time after a `:` is not a number of seconds.
+    InvalidDwell(String),
+    UnknownScreen(String),
+}
+
+impl fmt::Display for PlaylistError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PlaylistError::InvalidDwell(slide) => {
+                write!(f, "`{slide}` should be <screen>[:<seconds>]")
+            }
+            PlaylistError::UnknownScreen(screen) => write!(f, "no screen `{screen}`"),
+        }
+    }
+}
+
+impl std::error::Error for PlaylistError {}
+
+/// Parses a comma separated list of screens with optional dwell times in seconds,
+/// e.g. `logo:5, chart, system:15`.
+pub fn parse_playlist(text: &str) -> Result<Vec<Slide>, PlaylistError> {
+    text.split(',')
+        .map(str::trim)
+        .filter(|slide| !slide.is_empty())
+        .map(|slide| {
+            let (screen, dwell) = match slide.split_once(':') {
+                Some((screen, seconds)) => {
+                    let seconds = seconds
+                        .trim()
+                        .parse()
+                        .map_err(|_| PlaylistError::InvalidDwell(slide.to_string()))?;
+                    (screen, Some(Duration::from_secs(seconds)))
+                }
+                None => (slide, None),
+            };
+            Ok(Slide {
+                screen: screen.trim().to_string(),
+                dwell,
+            })
+        })
+        .collect()
+}
+
+/// Formats a playlist the way [`parse_playlist`] reads it.
+pub fn format_playlist(playlist: &[Slide]) -> String {
+    playlist
+        .iter()
+        .map(|slide| match slide.dwell {
+            Some(dwell) => format!("{}:{}", slide.screen, dwell.as_secs()),
+            None => slide.screen.clone(),
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Checks that every slide names one of `screens`.
+pub fn validate_playlist(playlist: &[Slide], screens: &[&str]) -> Result<(), PlaylistError> {
+    match playlist
+        .iter()
+        .find(|slide| !screens.contains(&slide.screen.as_str()))
+    {
+        Some(slide) => Err(PlaylistError::UnknownScreen(slide.screen.clone())),
+        None => Ok(()),
+    }
+}
+
+/// Moves through the playlist on its own, pausing while someone uses the button.
+#[derive(Debug, Clone)]
+pub struct Carousel {
+    pub config: CarouselConfig,
+    /// Indexes into the playlist in the order of the current round.
+    order: Vec<usize>,
+    position: usize,
+    shown_at: Instant,
+    paused_at: Option<Instant>,
+}
+
+impl Carousel {
+    pub fn new(config: CarouselConfig, now: Instant) -> Self {
+        Self {
+            config,
+            order: Vec::new(),
+            position: 0,
+            shown_at: now,
+            paused_at: None,
+        }
+    }
+
+    /// Records a button press at `now`, which pauses the carousel.
+    pub fn activity(&mut self, now: Instant) {
+        if self.config.enabled {
+            self.paused_at = Some(now);
+        }
+    }
+
+    /// Whether the carousel is enabled and not paused at `now`.
+    pub fn is_running(&self, now: Instant) -> bool {
+        self.config.enabled
+            && self
+                .paused_at
+                .is_none_or(|at| now.saturating_duration_since(at) >= self.config.resume_after)
+    }
+
+    /// Returns the index into `screens` to show next once the current slide's
+    /// dwell time is up at `now`.
+    pub fn update(&mut self, now: Instant, screens: &[&str]) -> Option<usize> {
+        if !self.is_running(now) {
+            // Whatever is on screen gets a full dwell once the carousel runs again.
+            self.shown_at = now;
+            return None;
+        }
+        if self.paused_at.take().is_some() {
+            self.shown_at = now;
+        }
+
+        let slides = self.slides(screens);
+        if slides.is_empty() {
+            return None;
+        }
+        if self.order.len() != slides.len() {
+            self.start_round(slides.len());
+        }
+        let (_, dwell) = slides[self.order[self.position]];
+        if now.saturating_duration_since(self.shown_at) < dwell {
+            return None;
+        }
+        self.position += 1;
+        if self.position == self.order.len() {
+            self.start_round(slides.len());
+        }
+        self.shown_at = now;
+        Some(slides[self.order[self.position]].0)
+    }
+
+    /// Screen index and dwell time of every slide that names one of `screens`.
+    fn slides(&self, screens: &[&str]) -> Vec<(usize, Duration)> {
+        let config = &self.config;
+        if config.playlist.is_empty() {
+            return (0..screens.len())
+                .map(|index| (index, config.dwell))
+                .collect();
+        }
+        config
+            .playlist
+            .iter()
+            .filter_map(|slide| {
+                let index = screens.iter().position(|&name| name == slide.screen)?;
+                Some((index, slide.dwell.unwrap_or(config.dwell)))
+            })
+            .collect()
+    }
+
+    fn start_round(&mut self, len: usize) {
+        self.order = (0..len).collect();
+        if self.config.shuffle {
+            self.order.shuffle(&mut rand::rng());
+        }
+        self.position = 0;
+    }
+}
+
+impl Default for Carousel {
+    fn default() -> Self {
+        Self::new(CarouselConfig::default(), Instant::now())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCREENS: [&str; 3] = ["logo", "chart", "system"];
+
+    fn secs(seconds: u64) -> Duration {
+        Duration::from_secs(seconds)
+    }
+
+    fn carousel(playlist: &str, start: Instant) -> Carousel {
+        let config = CarouselConfig {
+            enabled: true,
+            playlist: parse_playlist(playlist).unwrap(),
+            ..CarouselConfig::default()
+        };
+        Carousel::new(config, start)
+    }
+
+    #[test]
+    fn parses_playlists() {
+        let playlist = parse_playlist(" logo:5, chart ,").unwrap();
+        assert_eq!(
+            playlist,
+            [
+                Slide {
+                    screen: "logo".into(),
+                    dwell: Some(secs(5)),
+                },
+                Slide {
+                    screen: "chart".into(),
+                    dwell: None,
+                },
+            ]
+        );
+        assert_eq!(format_playlist(&playlist), "logo:5,chart");
+        assert_eq!(
+            parse_playlist("logo:soon"),
+            Err(PlaylistError::InvalidDwell("logo:soon".into()))
+        );
+        assert_eq!(
+            validate_playlist(&parse_playlist("clock").unwrap(), &SCREENS),
+            Err(PlaylistError::UnknownScreen("clock".into()))
+        );
+    }
+
+    #[test]
+    fn advances_after_each_dwell() {
+        let start = Instant::now();
+        let mut carousel = carousel("system:5, logo", start);
+        assert_eq!(carousel.update(start + secs(4), &SCREENS), None);
+        assert_eq!(carousel.update(start + secs(5), &SCREENS), Some(0));
+        assert_eq!(carousel.update(start + secs(14), &SCREENS), None);
+        assert_eq!(carousel.update(start + secs(15), &SCREENS), Some(2));
+    }
+
+    #[test]
+    fn press_pauses_until_idle() {
+        let start = Instant::now();
+        let mut carousel = carousel("", start);
+        carousel.activity(start + secs(8));
+        assert!(!carousel.is_running(start + secs(20)));
+        assert_eq!(carousel.update(start + secs(20), &SCREENS), None);
+        assert_eq!(carousel.update(start + secs(38), &SCREENS), None);
+        assert_eq!(carousel.update(start + secs(47), &SCREENS), None);
+        assert_eq!(carousel.update(start + secs(48), &SCREENS), Some(1));
+    }
+
+    #[test]
+    fn shuffles_every_slide_once_per_round() {
+        let start = Instant::now();
+        let mut carousel = carousel("logo:1, chart:1, system:1", start);
+        carousel.config.shuffle = true;
+        let mut shown: Vec<usize> = (1..=6)
+            .filter_map(|second| carousel.update(start + secs(second), &SCREENS))
+            .collect();
+        assert_eq!(shown.len(), 6);
+        // The first round began with the screen already shown, so check the second.
+        shown[2..5].sort();
+        assert_eq!(shown[2..5], [0, 1, 2]);
+    }
+}
@@ -1,152 +1,212 @@
+use crate::axis::{AxisScale, even_labels};
 use crate::helpers::center;
+use crate::input::InputEvent;
 use crate::lorem::LOREM_IPSUM;
-use esp_idf_svc::hal::delay;
-use esp_idf_svc::hal::gpio::{Gpio0, Input, PinDriver};
-use esp_idf_svc::hal::task::notification::Notification;
+use crate::screen::{Action, Context, Screen};
+use crate::source::{DataSource, SineSource};
 use mousefood::prelude::*;
+use mousefood::ratatui::symbols::Marker;
 use mousefood::ratatui::widgets::{Axis, Block, Chart, Clear, Dataset, Paragraph, Wrap};
-use std::io::Result;
-use std::marker::PhantomData;
-
-pub struct ChartApp<B: Backend> {
-    signal1: SinSignal,
-    data1: Vec<(f64, f64)>,
-    signal2: SinSignal,
-    data2: Vec<(f64, f64)>,
-    window: [f64; 2],
-    popup: bool,
-    _marker: PhantomData<B>,
-}
+use std::time::Duration;
+
+/// Time span shown by the chart, in seconds.
+const WINDOW: f64 = 20.0;
+/// Most y ticks that stay readable in the chart's height at 240x135.
+const Y_TICKS: usize = 5;
+const X_LABELS: usize = 3;
 
-#[derive(Clone)]
-struct SinSignal {
-    x: f64,
-    interval: f64,
-    period: f64,
-    scale: f64,
+/// A named line on the chart fed by a [`DataSource`].
+pub struct Series {
+    name: String,
+    marker: Marker,
+    /// Fixed colour, otherwise the theme's colour for the series' position.
+    color: Option<Color>,
+    source: Box<dyn DataSource>,
+    data: Vec<(f64, f64)>,
 }
 
-impl SinSignal {
-    const fn new(interval: f64, period: f64, scale: f64) -> Self {
+impl Series {
+    pub fn new(name: impl Into<String>, marker: Marker, source: impl DataSource + 'static) -> Self {
         Self {
-            x: 0.0,
-            interval,
-            period,
-            scale,
+            name: name.into(),
+            marker,
+            color: None,
+            source: Box::new(source),
+            data: Vec::new(),
         }
     }
-}
 
-impl Iterator for SinSignal {
-    type Item = (f64, f64);
-    fn next(&mut self) -> Option<Self::Item> {
-        let point = (self.x, (self.x * 1.0 / self.period).sin() * self.scale);
-        self.x += self.interval;
-        Some(point)
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = Some(color);
+        self
     }
 }
 
-impl<B: Backend> ChartApp<B> {
+pub struct ChartApp {
+    series: Vec<Series>,
+    /// Unit of the values, used in the y labels.
+    unit: &'static str,
+    /// Space above and below the data as a fraction of its range.
+    headroom: f64,
+    /// Time since the chart started, in seconds.
+    elapsed: Duration,
+    popup: bool,
+}
+
+impl ChartApp {
     pub fn new() -> Self {
-        let mut signal1 = SinSignal::new(0.2, 3.0, 18.0);
-        let mut signal2 = SinSignal::new(0.1, 2.0, 10.0);
-        let data1 = signal1.by_ref().take(200).collect::<Vec<(f64, f64)>>();
-        let data2 = signal2.by_ref().take(200).collect::<Vec<(f64, f64)>>();
+        Self::with_series(vec![
+            Series::new("data2", Marker::Dot, SineSource::new(0.2, 3.0, 18.0)),
+            Series::new("data3", Marker::Braille, SineSource::new(0.1, 2.0, 10.0)),
+        ])
+    }
 
-        Self {
-            signal1,
-            data1,
-            signal2,
-            data2,
-            window: [0.0, 20.0],
+    pub fn with_series(series: Vec<Series>) -> Self {
+        let mut app = Self {
+            series,
+            unit: "",
+            headroom: 0.1,
+            elapsed: Duration::ZERO,
             popup: false,
-            _marker: PhantomData,
+        };
+        app.restart(&Context::default());
+        app
+    }
+
+    pub fn with_unit(mut self, unit: &'static str) -> Self {
+        self.unit = unit;
+        self
+    }
+
+    pub fn with_headroom(mut self, headroom: f64) -> Self {
+        self.headroom = headroom;
+        self
+    }
+
+    /// Starts with a full window of samples.
+    fn restart(&mut self, ctx: &Context) {
+        self.elapsed = Duration::from_secs_f64(WINDOW);
+        self.popup = false;
+        for series in &mut self.series {
+            series.source.reset();
+            series.data.clear();
         }
+        self.pull(ctx);
+    }
+
+    fn pull(&mut self, ctx: &Context) {
+        let now = self.elapsed.as_secs_f64();
+        let mut samples = Vec::new();
+        for series in &mut self.series {
+            samples.clear();
+            series.source.pull(self.elapsed, ctx, &mut samples);
+            series
+                .data
+                .extend(samples.iter().map(|sample| (sample.time, sample.value)));
+            let expired = series
+                .data
+                .iter()
+                .take_while(|&&(time, _)| time < now - WINDOW)
+                .count();
+            series.data.drain(..expired);
+        }
+    }
+
+    fn window(&self) -> [f64; 2] {
+        let now = self.elapsed.as_secs_f64();
+        [now - WINDOW, now]
+    }
+}
+
+impl Default for ChartApp {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Screen for ChartApp {
+    fn name(&self) -> &'static str {
+        "chart"
+    }
+
+    fn init(&mut self, ctx: &mut Context) {
+        self.restart(ctx);
     }
 
-    pub fn run(
-        mut self,
-        terminal: &mut Terminal<B>,
-        notification: &mut Notification,
-        button: &mut PinDriver<Gpio0, Input>,
-    ) -> Result<()> {
-        button.enable_interrupt().unwrap();
-        loop {
-            if notification.wait(delay::NON_BLOCK).is_some() {
-                if self.popup {
-                    return Ok(());
-                }
-                self.popup = true;
-                button.enable_interrupt().unwrap();
+    fn handle_event(&mut self, event: InputEvent, _ctx: &mut Context) -> Action {
+        match event {
+            InputEvent::ShortPress => {
+                self.popup = !self.popup;
+                Action::Stay
             }
-            terminal.draw(|frame| self.draw(frame))?;
-            self.on_tick();
+            InputEvent::LongPress => Action::NextScreen,
+            InputEvent::DoublePress => Action::PreviousScreen,
+            InputEvent::Repeat => Action::Stay,
+        }
+    }
+
+    fn tick(&mut self, dt: Duration, ctx: &mut Context) {
+        self.elapsed += dt;
+        self.pull(ctx);
+    }
+
+    fn hints(&self) -> &'static str {
+        if self.popup {
+            "[S1] hide  [hold] next"
+        } else {
+            "[S1] popup  [hold] next"
         }
     }
 
-    fn on_tick(&mut self) {
-        self.data1.drain(0..5);
-        self.data1.extend(self.signal1.by_ref().take(5));
-
-        self.data2.drain(0..10);
-        self.data2.extend(self.signal2.by_ref().take(10));
-
-        self.window[0] += 1.0;
-        self.window[1] += 1.0;
-    }
-
-    fn draw(&mut self, frame: &mut Frame) {
-        let x_labels = vec![
-            Span::styled(
-                format!("{}", self.window[0]),
-                Style::default().add_modifier(Modifier::BOLD),
-            ),
-            Span::raw(format!("{}", (self.window[0] + self.window[1]) / 2.0)),
-            Span::styled(
-                format!("{}", self.window[1]),
-                Style::default().add_modifier(Modifier::BOLD),
-            ),
-        ];
-        let datasets = vec![
-            Dataset::default()
-                .name("data2")
-                .marker(symbols::Marker::Dot)
-                .style(Style::default().fg(Color::Cyan))
-                .data(&self.data1),
-            Dataset::default()
-                .name("data3")
-                .marker(symbols::Marker::Braille)
-                .style(Style::default().fg(Color::Yellow))
-                .data(&self.data2),
-        ];
+    fn draw(&self, frame: &mut Frame, area: Rect, ctx: &Context) {
+        let theme = &ctx.theme;
+        let window = self.window();
+        // Relative to now, so the labels stay put while the window slides.
+        let x_labels = bold_ends(even_labels([-WINDOW, 0.0], X_LABELS, "s"));
+        let y_scale = AxisScale::auto(
+            self.series
+                .iter()
+                .flat_map(|series| series.data.iter().map(|&(_, value)| value)),
+            self.headroom,
+            Y_TICKS,
+        );
+        let y_labels = bold_ends(y_scale.labels(self.unit));
+        let datasets = self
+            .series
+            .iter()
+            .enumerate()
+            .map(|(index, series)| {
+                let color = series.color.unwrap_or(theme.series(index));
+                Dataset::default()
+                    .name(series.name.as_str())
+                    .marker(series.marker)
+                    .style(Style::default().fg(color))
+                    .data(&series.data)
+            })
+            .collect();
 
         let chart = Chart::new(datasets)
-            .block(Block::bordered())
+            .block(Block::bordered().border_style(theme.border))
             .x_axis(
                 Axis::default()
                     .title("X Axis")
-                    .style(Style::default().fg(Color::Gray))
+                    .style(Style::default().fg(theme.footer))
                     .labels(x_labels)
-                    .bounds(self.window),
+                    .bounds(window),
             )
             .y_axis(
                 Axis::default()
                     .title("Y Axis")
-                    .style(Style::default().fg(Color::Gray))
-                    .labels(["-20".bold(), "0".into(), "20".bold()])
-                    .bounds([-20.0, 20.0]),
+                    .style(Style::default().fg(theme.footer))
+                    .labels(y_labels)
+                    .bounds(y_scale.bounds),
             );
 
-        let [top_area, footer_area] =
-            Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(frame.area());
-        frame.render_widget(chart, top_area);
+        frame.render_widget(chart, area);
 
         if self.popup {
-            let footer = Line::raw("[S1] to change screen").centered().gray();
-            frame.render_widget(footer, footer_area);
-
-            let style = Style::default().fg(Color::Black).bg(Color::Yellow);
-            let area = center(frame.area(), Constraint::Length(24), Constraint::Length(8));
+            let style = Style::default().fg(theme.background).bg(theme.accent);
+            let area = center(area, Constraint::Length(24), Constraint::Length(8));
             let block = Block::bordered().border_style(style).title("Popup!");
             let text = Paragraph::new(LOREM_IPSUM)
                 .block(block)
@@ -154,9 +214,22 @@ impl<B: Backend> ChartApp<B> {
                 .wrap(Wrap { trim: true });
             frame.render_widget(Clear, area);
             frame.render_widget(text, area);
-        } else {
-            let footer = Line::raw("[S1] to display popup").centered().gray();
-            frame.render_widget(footer, footer_area);
         }
     }
 }
+
+/// Axis labels with the outer ones in bold.
+pub(crate) fn bold_ends(labels: Vec<String>) -> Vec<Span<'static>> {
+    let last = labels.len().saturating_sub(1);
+    labels
+        .into_iter()
+        .enumerate()
+        .map(|(i, label)| {
+            if i == 0 || i == last {
+                label.bold()
+            } else {
+                label.into()
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,317 @@
+use std::fmt;
+use std::time::Instant;
+use time::{Date, Month, OffsetDateTime, PrimitiveDateTime, Time, UtcOffset};
+
+/// Where the wall-clock time was last set from, most trusted last.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TimeSource {
+    #[default]
+    Unset,
+    /// When the firmware was built, only good enough for the calendar.
+    CompileTime,
+    /// Kept by the RTC across deep sleep and resets.
+    RtcMemory,
+    /// The host's system clock in the simulator.
+    System,
+    /// Set with a command over the serial console.
+    Serial,
+}
+
+impl fmt::Display for TimeSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let source = match self {
+            TimeSource::Unset => "not set",
+            TimeSource::CompileTime => "build time",
+            TimeSource::RtcMemory => "RTC",
+            TimeSource::System => "system",
+            TimeSource::Serial => "serial",
+        };
+        f.write_str(source)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeError {
+    /// Neither a Unix timestamp nor `YYYY-MM-DD[ HH:MM[:SS]]`.
+    InvalidFormat,
+    /// The numbers don't make a valid date, time or offset.
+    OutOfRange,
+}
+
+impl fmt::Display for TimeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TimeError::InvalidFormat => {
+                write!(f, "expected a Unix timestamp or YYYY-MM-DD HH:MM[:SS]")
+            }
+            TimeError::OutOfRange => write!(f, "date, time or offset out of range"),
+        }
+    }
+}
+
+impl std::error::Error for TimeError {}
+
+/// Wall-clock time for a board without a battery backed RTC.
+///
+/// Keeps the UTC time it was last set to together with the [`Instant`] it was
+/// set at, and shows local time with a fixed UTC offset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Clock {
+    synced: Option<(OffsetDateTime, Instant)>,
+    offset: UtcOffset,
+    source: TimeSource,
+}
+
+impl Clock {
+    /// Sets the time to `utc` as of `now`.
+    pub fn set(&mut self, utc: OffsetDateTime, source: TimeSource, now: Instant) {
+        self.synced = Some((utc.to_offset(UtcOffset::UTC), now));
+        self.source = source;
+    }
+
+    pub fn source(&self) -> TimeSource {
+        self.source
+    }
+
+    /// Offset of the local timezone from UTC.
+    pub fn offset(&self) -> UtcOffset {
+        self.offset
+    }
+
+    pub fn set_offset(&mut self, offset: UtcOffset) {
+        self.offset = offset;
+    }
+
+    /// UTC time at `now`, `None` until the clock was set.
+    pub fn utc_at(&self, now: Instant) -> Option<OffsetDateTime> {
+        let (utc, at) = self.synced?;
+        // Instants before the sync only happen in tests, which don't care.
+        Some(utc + now.saturating_duration_since(at))
+    }
+
+    /// Local time at `now`, `None` until the clock was set.
+    pub fn local_at(&self, now: Instant) -> Option<OffsetDateTime> {
+        Some(self.utc_at(now)?.to_offset(self.offset))
+    }
+
+    /// Local time right now.
+    pub fn now(&self) -> Option<OffsetDateTime> {
+        self.local_at(Instant::now())
+    }
+}
+
+impl Default for Clock {
+    fn default() -> Self {
+        Self {
+            synced: None,
+            offset: UtcOffset::UTC,
+            source: TimeSource::Unset,
+        }
+    }
+}
+
+/// Roughly when this firmware was built, as recorded by the build script the
+/// last time the sources changed.
+pub fn build_time() -> Option<OffsetDateTime> {
+    let timestamp = option_env!("BUILD_UNIX_TIME")?.parse().ok()?;
+    OffsetDateTime::from_unix_timestamp(timestamp).ok()
+}
+
+/// Parses a Unix timestamp, or a date with an optional time of day in `offset`
+/// unless it ends with `Z` or its own offset, e.g. `2025-05-23 14:05`,
+/// `2025-05-23T14:05:30+02:00`.
+pub fn parse_time(text: &str, offset: UtcOffset) -> Result<OffsetDateTime, TimeError> {
+    let text = text.trim();
+    if let Ok(timestamp) = text.parse::<i64>() {
+        return OffsetDateTime::from_unix_timestamp(timestamp).map_err(|_| TimeError::OutOfRange);
+    }
+
+    let split = if text.is_char_boundary(10) {
+        10
+    } else {
+        text.len()
+    };
+    let (date, rest) = text.split_at(split);
+    let date = parse_date(date)?;
+    let rest = rest.strip_prefix(['T', ' ']).unwrap_or(rest);
+    // The offset starts at the first sign or `Z` after the time of day.
+    let split = rest.find(['Z', 'z', '+', '-']).unwrap_or(rest.len());
+    let (time, zone) = rest.split_at(split);
+    let time = if time.is_empty() {
+        Time::MIDNIGHT
+    } else {
+        parse_time_of_day(time)?
+    };
+    let offset = if zone.is_empty() {
+        offset
+    } else {
+        parse_offset(zone)?
+    };
+    Ok(PrimitiveDateTime::new(date, time).assume_offset(offset))
+}
+
+/// Parses a UTC offset like `Z`, `UTC`, `+2`, `UTC+02:00`, `-0530` or `+5:45`.
+pub fn parse_offset(text: &str) -> Result<UtcOffset, TimeError> {
+    let text = text.trim();
+    let text = text
+        .strip_prefix("UTC")
+        .or_else(|| text.strip_prefix("GMT"))
+        .unwrap_or(text);
+    if text.is_empty() || text.eq_ignore_ascii_case("z") {
+        return Ok(UtcOffset::UTC);
+    }
+    let (sign, digits) = if let Some(digits) = text.strip_prefix('+') {
+        (1, digits)
+    } else if let Some(digits) = text.strip_prefix('-') {
+        (-1, digits)
+    } else {
+        return Err(TimeError::InvalidFormat);
+    };
+    let (hours, minutes) = match digits.split_once(':') {
+        Some(parts) => parts,
+        None if digits.len() == 4 => digits.split_at(2),
+        None => (digits, "0"),
+    };
+    let hours: i8 = parse_number(hours)?;
+    let minutes: i8 = parse_number(minutes)?;
+    UtcOffset::from_hms(sign * hours, sign * minutes, 0).map_err(|_| TimeError::OutOfRange)
+}
+
+/// Formats an offset the way [`parse_offset`] reads it, e.g. `+02:00`.
+pub fn format_offset(offset: UtcOffset) -> String {
+    let (hours, minutes, _) = offset.as_hms();
+    let sign = if offset.is_negative() { '-' } else { '+' };
+    format!(
+        "{sign}{:02}:{:02}",
+        hours.unsigned_abs(),
+        minutes.unsigned_abs()
+    )
+}
+
+fn parse_date(text: &str) -> Result<Date, TimeError> {
+    let mut parts = text.split('-');
+    let (Some(year), Some(month), Some(day), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return Err(TimeError::InvalidFormat);
+    };
+    let month = Month::try_from(parse_number::<u8>(month)?).map_err(|_| TimeError::OutOfRange)?;
+    Date::from_calendar_date(parse_number(year)?, month, parse_number(day)?)
+        .map_err(|_| TimeError::OutOfRange)
+}
+
+fn parse_time_of_day(text: &str) -> Result<Time, TimeError> {
+    let mut parts = text.split(':');
+    let (Some(hour), Some(minute), second, None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return Err(TimeError::InvalidFormat);
+    };
+    let second = second.map_or(Ok(0), parse_number)?;
+    Time::from_hms(parse_number(hour)?, parse_number(minute)?, second)
+        .map_err(|_| TimeError::OutOfRange)
+}
+
+fn parse_number<T: std::str::FromStr>(text: &str) -> Result<T, TimeError> {
+    if text.is_empty() || !text.bytes().all(|byte| byte.is_ascii_digit()) {
+        return Err(TimeError::InvalidFormat);
+    }
+    text.parse().map_err(|_| TimeError::OutOfRange)
+}
+
+/// First day of the month `months` after the month of `date`, or before if negative.
+pub fn add_months(date: Date, months: i32) -> Date {
+    let index = date.year() * 12 + i32::from(u8::from(date.month())) - 1 + months;
+    let month = Month::try_from((index.rem_euclid(12) + 1) as u8).expect("month is 1 to 12");
+    Date::from_calendar_date(index.div_euclid(12), month, 1).unwrap_or(date)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn date(year: i32, month: u8, day: u8) -> Date {
+        Date::from_calendar_date(year, Month::try_from(month).unwrap(), day).unwrap()
+    }
+
+    fn datetime(
+        date: Date,
+        (hour, minute, second): (u8, u8, u8),
+        offset: UtcOffset,
+    ) -> OffsetDateTime {
+        PrimitiveDateTime::new(date, Time::from_hms(hour, minute, second).unwrap())
+            .assume_offset(offset)
+    }
+
+    fn hours(hours: i8, minutes: i8) -> UtcOffset {
+        UtcOffset::from_hms(hours, minutes, 0).unwrap()
+    }
+
+    #[test]
+    fn keeps_time_after_set() {
+        let start = Instant::now();
+        let mut clock = Clock::default();
+        assert_eq!(clock.local_at(start), None);
+        let utc = datetime(date(2025, 5, 23), (23, 30, 0), UtcOffset::UTC);
+        clock.set(utc, TimeSource::Serial, start);
+        clock.set_offset(hours(2, 0));
+        let later = start + Duration::from_secs(90);
+        assert_eq!(clock.utc_at(later), Some(utc + Duration::from_secs(90)));
+        let local = clock.local_at(later).unwrap();
+        assert_eq!(local.date(), date(2025, 5, 24));
+        assert_eq!(local.offset(), hours(2, 0));
+    }
+
+    #[test]
+    fn parses_times() {
+        let cest = hours(2, 0);
+        let day = date(2025, 5, 23);
+        assert_eq!(
+            parse_time("1748000000", cest),
+            Ok(datetime(day, (11, 33, 20), UtcOffset::UTC))
+        );
+        assert_eq!(
+            parse_time("2025-05-23 14:05", cest),
+            Ok(datetime(day, (14, 5, 0), cest))
+        );
+        assert_eq!(
+            parse_time("2025-05-23T14:05:30Z", cest),
+            Ok(datetime(day, (14, 5, 30), UtcOffset::UTC))
+        );
+        assert_eq!(
+            parse_time("2025-05-23T14:05-05:30", cest),
+            Ok(datetime(day, (14, 5, 0), hours(-5, -30)))
+        );
+        assert_eq!(
+            parse_time("2025-05-23", cest),
+            Ok(datetime(day, (0, 0, 0), cest))
+        );
+        assert_eq!(parse_time("2025-02-30", cest), Err(TimeError::OutOfRange));
+        assert_eq!(parse_time("yesterday", cest), Err(TimeError::InvalidFormat));
+        assert_eq!(
+            parse_time("2025-05-2é", cest),
+            Err(TimeError::InvalidFormat)
+        );
+    }
+
+    #[test]
+    fn parses_offsets() {
+        assert_eq!(parse_offset("Z"), Ok(UtcOffset::UTC));
+        assert_eq!(parse_offset("UTC+2"), Ok(hours(2, 0)));
+        assert_eq!(parse_offset("-0530"), Ok(hours(-5, -30)));
+        assert_eq!(parse_offset("+5:45"), Ok(hours(5, 45)));
+        assert_eq!(parse_offset("+30"), Err(TimeError::OutOfRange));
+        assert_eq!(parse_offset("2"), Err(TimeError::InvalidFormat));
+        assert_eq!(format_offset(hours(-5, -30)), "-05:30");
+        assert_eq!(parse_offset(&format_offset(hours(5, 45))), Ok(hours(5, 45)));
+    }
+
+    #[test]
+    fn adds_months_across_years() {
+        let day = date(2025, 5, 23);
+        assert_eq!(add_months(day, 0), date(2025, 5, 1));
+        assert_eq!(add_months(day, 8), date(2026, 1, 1));
+        assert_eq!(add_months(day, -5), date(2024, 12, 1));
+    }
+}
@@ -0,0 +1,214 @@
+use crate::input::InputEvent;
+use crate::metrics::format_uptime;
+use crate::overlay::Overlay;
+use crate::screen::Context;
+use mousefood::prelude::*;
+use mousefood::ratatui::widgets::{Block, Clear, Padding, Paragraph, Wrap};
+use std::fmt;
+use std::panic::PanicHookInfo;
+use std::time::Duration;
+
+/// What a panic left behind, kept across the reset that follows it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrashReport {
+    pub message: String,
+    /// `file:line:column` of the panic.
+    pub location: Option<String>,
+    /// Time since boot when it panicked.
+    pub uptime: Duration,
+}
+
+impl CrashReport {
+    pub fn from_panic(info: &PanicHookInfo, uptime: Duration) -> Self {
+        let payload = info.payload();
+        let message = match payload.downcast_ref::<&str>() {
+            Some(message) => message.to_string(),
+            None => match payload.downcast_ref::<String>() {
+                Some(message) => message.clone(),
+                None => "Box<dyn Any>".to_string(),
+            },
+        };
+        Self {
+            message,
+            location: info.location().map(ToString::to_string),
+            uptime,
+        }
+    }
+
+    /// `uptime=` and `location=` lines followed by the message, cut to at most
+    /// `max_len` bytes.
+    pub fn to_bytes(&self, max_len: usize) -> Vec<u8> {
+        let mut record = format!(
+            "uptime={}\nlocation={}\n{}",
+            self.uptime.as_millis(),
+            self.location.as_deref().unwrap_or_default(),
+            self.message
+        );
+        let mut len = record.len().min(max_len);
+        while !record.is_char_boundary(len) {
+            len -= 1;
+        }
+        record.truncate(len);
+        record.into_bytes()
+    }
+
+    /// Reads a record written by [`CrashReport::to_bytes`], `None` if it isn't one.
+    pub fn from_bytes(record: &[u8]) -> Option<Self> {
+        let record = std::str::from_utf8(record).ok()?;
+        let mut lines = record.splitn(3, '\n');
+        let uptime = lines.next()?.strip_prefix("uptime=")?.parse().ok()?;
+        let location = lines.next()?.strip_prefix("location=")?;
+        Some(Self {
+            message: lines.next().unwrap_or_default().to_string(),
+            location: (!location.is_empty()).then(|| location.to_string()),
+            uptime: Duration::from_millis(uptime),
+        })
+    }
+}
+
+impl fmt::Display for CrashReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "panic")?;
+        if let Some(location) = &self.location {
+            write!(f, " at {location}")?;
+        }
+        write!(f, " after {}: {}", format_uptime(self.uptime), self.message)
+    }
+}
+
+/// Red full screen with a [`CrashReport`], drawn without the theme since it may
+/// be all that still works.
+#[derive(Debug, Clone)]
+pub struct CrashScreen<'a> {
+    report: &'a CrashReport,
+    after_reset: bool,
+}
+
+impl<'a> CrashScreen<'a> {
+    pub fn new(report: &'a CrashReport) -> Self {
+        Self {
+            report,
+            after_reset: false,
+        }
+    }
+
+    /// Shows the report of a panic before the last reset, dismissed with S1.
+    pub fn after_reset(mut self) -> Self {
+        self.after_reset = true;
+        self
+    }
+}
+
+impl Widget for CrashScreen<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let report = self.report;
+        let style = Style::new().fg(Color::White).bg(Color::Red);
+        let mut block = Block::bordered()
+            .border_set(symbols::border::PROPORTIONAL_TALL)
+            .padding(Padding::horizontal(1))
+            .style(style);
+        block = if self.after_reset {
+            block
+                .title(" Panicked before the last reset ".bold())
+                .title_bottom(Line::from(" [S1] dismiss ").right_aligned())
+        } else {
+            block.title(" Panic ".bold())
+        };
+
+        let location = report.location.as_deref().unwrap_or("unknown location");
+        let details = vec![
+            Line::from(format!("at {location}")),
+            Line::from(format!("after {} uptime", format_uptime(report.uptime))),
+        ];
+        let inner = block.inner(area);
+        block.render(area, buf);
+        let [message_area, details_area] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(2)]).areas(inner);
+        Paragraph::new(report.message.as_str())
+            .wrap(Wrap { trim: false })
+            .render(message_area, buf);
+        Paragraph::new(details)
+            .wrap(Wrap { trim: false })
+            .render(details_area, buf);
+    }
+}
+
+/// Shows the report of a panic before the last reset on top of everything until
+/// S1 is pressed.
+#[derive(Debug, Clone)]
+pub struct CrashOverlay {
+    report: Option<CrashReport>,
+}
+
+impl CrashOverlay {
+    pub fn new(report: CrashReport) -> Self {
+        Self {
+            report: Some(report),
+        }
+    }
+}
+
+impl Overlay for CrashOverlay {
+    fn handle_event(&mut self, _event: InputEvent, _ctx: &mut Context) -> bool {
+        self.report.take().is_some()
+    }
+
+    fn draw(&self, frame: &mut Frame, _ctx: &Context) {
+        if let Some(report) = &self.report {
+            frame.render_widget(Clear, frame.area());
+            frame.render_widget(CrashScreen::new(report).after_reset(), frame.area());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report() -> CrashReport {
+        CrashReport {
+            message: "called `Result::unwrap()` on an `Err` value: Timeout".into(),
+            location: Some("src/firmware.rs:201:54".into()),
+            uptime: Duration::from_secs(312),
+        }
+    }
+
+    #[test]
+    fn reads_what_it_wrote() {
+        let report = report();
+        assert_eq!(
+            CrashReport::from_bytes(&report.to_bytes(512)),
+            Some(report.clone())
+        );
+        let unknown = CrashReport {
+            location: None,
+            ..report
+        };
+        assert_eq!(
+            CrashReport::from_bytes(&unknown.to_bytes(512)),
+            Some(unknown)
+        );
+        assert_eq!(CrashReport::from_bytes(b"\xff\x00garbage"), None);
+    }
+
+    #[test]
+    fn cuts_long_messages_between_characters() {
+        let report = CrashReport {
+            message: "é".repeat(100),
+            ..report()
+        };
+        let record = report.to_bytes(64);
+        assert!(record.len() <= 64);
+        let read = CrashReport::from_bytes(&record).unwrap();
+        assert!(report.message.starts_with(&read.message));
+    }
+
+    #[test]
+    fn formats_as_a_log_line() {
+        assert_eq!(
+            report().to_string(),
+            "panic at src/firmware.rs:201:54 after 5m12s: \
+             called `Result::unwrap()` on an `Err` value: Timeout"
+        );
+    }
+}
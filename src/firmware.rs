@@ -0,0 +1,368 @@
+mod clock;
+mod crash;
+mod metrics;
+mod serial;
+mod settings;
+
+use crash::SharedDisplay;
+use embedded_graphics::pixelcolor::Rgb565;
+use embedded_graphics::prelude::Size;
+use esp_idf_svc::hal::adc::attenuation;
+use esp_idf_svc::hal::adc::oneshot::config::{AdcChannelConfig, Calibration as AdcCalibration};
+use esp_idf_svc::hal::adc::oneshot::{AdcChannelDriver, AdcDriver};
+use esp_idf_svc::hal::adc::{ADC1, Resolution};
+use esp_idf_svc::hal::delay::Ets;
+use esp_idf_svc::hal::gpio::{
+    ADCPin, AnyIOPin, AnyInputPin, AnyOutputPin, Output, PinDriver, Pins,
+};
+use esp_idf_svc::hal::ledc::config::TimerConfig;
+use esp_idf_svc::hal::ledc::{LedcDriver, LedcTimerDriver};
+use esp_idf_svc::hal::prelude::*;
+use esp_idf_svc::hal::spi::config::MODE_3;
+use esp_idf_svc::hal::spi::{SpiConfig, SpiDeviceDriver, SpiDriver, SpiDriverConfig};
+use esp_idf_svc::nvs::EspDefaultNvsPartition;
+use esp_idf_svc::sys::{self, EspError, adc_atten_t, esp};
+use metrics::EspMetrics;
+use mipidsi::Builder;
+use mipidsi::interface::SpiInterface;
+use mipidsi::models::{ILI9342CRgb565, Model, ST7789};
+use mipidsi::options::Orientation;
+use mousefood::prelude::*;
+use mousefood::ratatui::symbols::Marker;
+use mousefood_esp32_demo::app::{App, demo_screens};
+use mousefood_esp32_demo::board::{AdcAttenuation, BatterySense, BoardProfile, PanelModel};
+use mousefood_esp32_demo::calibration::Calibration;
+use mousefood_esp32_demo::carousel::CarouselConfig;
+use mousefood_esp32_demo::chart::{ChartApp, Series};
+use mousefood_esp32_demo::crash::CrashOverlay;
+use mousefood_esp32_demo::input::{ButtonTimings, ChordInput, GpioButton};
+use mousefood_esp32_demo::overlay::LowBatteryOverlay;
+use mousefood_esp32_demo::power::{PowerState, SleepMode};
+use mousefood_esp32_demo::screenshot::{self, ScreenshotFormat};
+use mousefood_esp32_demo::settings::{Settings, SettingsStore};
+use mousefood_esp32_demo::shell;
+use mousefood_esp32_demo::source::{self, BatterySource, LineSource};
+use settings::NvsBackend;
+use std::io;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+type DisplayInterface = SpiInterface<
+    'static,
+    SpiDeviceDriver<'static, SpiDriver<'static>>,
+    PinDriver<'static, AnyOutputPin, Output>,
+>;
+type ResetPin = PinDriver<'static, AnyOutputPin, Output>;
+type BatteryReader = Box<dyn FnMut() -> Option<u16>>;
+type Button = ChordInput<GpioButton, Box<dyn FnMut() -> bool>>;
+
+/// Everything the run loop drives besides the display.
+struct Devices {
+    /// Shared with the panic hook, which turns it up to show the panic.
+    backlight: Arc<Mutex<Backlight>>,
+    button: Button,
+    read_battery: BatteryReader,
+    settings: SettingsStore<NvsBackend>,
+    console: Receiver<String>,
+    /// Values in volts for the chart's serial series.
+    plot: Sender<String>,
+}
+
+/// Display backlight dimmed with LEDC PWM.
+struct Backlight {
+    driver: LedcDriver<'static>,
+    percent: u8,
+}
+
+impl Backlight {
+    fn set(&mut self, percent: u8) -> Result<(), EspError> {
+        if percent == self.percent {
+            return Ok(());
+        }
+        let duty = self.driver.get_max_duty() * u32::from(percent.min(100)) / 100;
+        self.driver.set_duty(duty)?;
+        self.percent = percent;
+        Ok(())
+    }
+}
+
+pub fn run() {
+    esp_idf_svc::sys::link_patches();
+    esp_idf_svc::log::EspLogger::initialize_default();
+
+    let board = BoardProfile::selected();
+    board.validate().expect("Invalid board profile");
+    let pins = board.pins;
+    let peripherals = Peripherals::take().unwrap();
+
+    // Turn on display backlight
+    let timer = LedcTimerDriver::new(
+        peripherals.ledc.timer0,
+        &TimerConfig::new().frequency(25.kHz().into()),
+    )
+    .unwrap();
+    let driver =
+        LedcDriver::new(peripherals.ledc.channel0, timer, output_pin(pins.backlight)).unwrap();
+    let mut backlight = Backlight { driver, percent: 0 };
+    backlight.set(100).unwrap();
+
+    // Configure SPI
+    let config = SpiConfig::new()
+        .write_only(true)
+        .baudrate(80u32.MHz().into())
+        .data_mode(MODE_3);
+    let spi_device = SpiDeviceDriver::new_single(
+        peripherals.spi2,
+        output_pin(pins.sclk),
+        output_pin(pins.mosi),
+        Option::<AnyIOPin>::None,
+        Some(output_pin(pins.cs)),
+        &SpiDriverConfig::new(),
+        &config,
+    )
+    .unwrap();
+    let buffer = Box::leak(Box::new([0_u8; 4096]));
+    let spi_interface = SpiInterface::new(
+        spi_device,
+        PinDriver::output(output_pin(pins.dc)).unwrap(),
+        buffer,
+    );
+    let reset = PinDriver::output(output_pin(pins.reset)).unwrap();
+
+    // Setup button
+    let button_pin = unsafe { AnyInputPin::new(pins.button.into()) };
+    let button = GpioButton::new(button_pin, ButtonTimings::default()).unwrap();
+
+    // Setup the screenshot chord, a long press of S1 while holding the second button
+    let chord = pins
+        .chord_button
+        .map(|pin| PinDriver::input(unsafe { AnyInputPin::new(pin.into()) }).unwrap());
+    let button: Button = ChordInput::new(
+        button,
+        Box::new(move || chord.as_ref().is_some_and(|pin| pin.is_low())),
+    );
+
+    // Setup battery voltage reader
+    let read_battery = battery_reader(peripherals.adc1, peripherals.pins, board.battery);
+
+    // Plot the battery and voltages sent over the serial console on the chart
+    let (plot, plot_lines) = mpsc::channel();
+    let chart = ChartApp::with_series(vec![
+        Series::new("serial", Marker::Braille, LineSource::new(plot_lines)),
+        Series::new(
+            "battery",
+            Marker::Dot,
+            BatterySource::new(Duration::from_secs(1)),
+        ),
+    ])
+    .with_unit("V");
+
+    // Restore the settings, including this device's battery calibration
+    let mut app = App::new(demo_screens(Box::new(EspMetrics::default()), chart))
+        .with_overlay(Box::new(LowBatteryOverlay::default()));
+    app.context_mut().calibration = Calibration::for_board(board.battery);
+    app.set_carousel(CarouselConfig::selected())
+        .expect("Invalid DEMO_PLAYLIST");
+    let nvs = EspDefaultNvsPartition::take().unwrap();
+    let mut settings = SettingsStore::new(NvsBackend::new(nvs).unwrap());
+    match settings.load() {
+        Ok(saved) => saved.apply(&mut app),
+        Err(error) => eprintln!("{error}, using the defaults"),
+    }
+
+    // Show the report of a panic that reset the board
+    if let Some(report) = crash::take_report() {
+        eprintln!("Reset after a {report}");
+        app = app.with_overlay(Box::new(CrashOverlay::new(report)));
+    }
+
+    // Setup wall-clock time and the serial console shell
+    clock::restore(&mut app.context_mut().clock);
+    let console = serial::console_lines();
+
+    let devices = Devices {
+        backlight: Arc::new(Mutex::new(backlight)),
+        button,
+        read_battery,
+        settings,
+        console,
+        plot,
+    };
+    match board.panel {
+        PanelModel::St7789 => run_display(ST7789, spi_interface, reset, board, app, devices),
+        PanelModel::Ili9342c => {
+            run_display(ILI9342CRgb565, spi_interface, reset, board, app, devices)
+        }
+    }
+}
+
+fn run_display<M>(
+    model: M,
+    interface: DisplayInterface,
+    reset: ResetPin,
+    board: &BoardProfile,
+    mut app: App,
+    mut devices: Devices,
+) -> !
+where
+    M: Model<ColorFormat = Rgb565> + 'static,
+{
+    // Configure display
+    let mut delay = Ets;
+    let display = Builder::new(model, interface)
+        .invert_colors(board.inversion)
+        .reset_pin(reset)
+        .display_offset(board.offset.0, board.offset.1)
+        .display_size(board.size.0, board.size.1)
+        .orientation(Orientation::new().rotate(board.rotation))
+        .init(&mut delay)
+        .expect("Failed to init display");
+
+    // Draw panics on the display from here on
+    let mut display = SharedDisplay::new(display);
+    crash::install_hook(display.clone(), Arc::clone(&devices.backlight));
+
+    // Setup Mousefood and Ratatui
+    let backend = EmbeddedBackend::new(&mut display, Default::default());
+    let mut terminal = Terminal::new(backend).unwrap();
+
+    let mut saved_clock = app.context().clock;
+    loop {
+        if let Some(voltage) = (devices.read_battery)() {
+            app.context_mut()
+                .push_battery_sample(voltage, Instant::now());
+        }
+        app.frame(&mut terminal, &mut devices.button).unwrap();
+        if let Some((format, buffer)) = app.take_screenshot() {
+            let (width, height) = board.resolution();
+            let size = Size::new(width.into(), height.into());
+            screenshot::export(format, &buffer, size, &mut io::stdout().lock()).unwrap();
+        }
+        if devices.button.take_triggered() {
+            app.request_screenshot(ScreenshotFormat::Bmp);
+        }
+        for line in devices.console.try_iter() {
+            // Numbers are values for the chart, anything else a command
+            if matches!(source::parse_line(&line), Ok(Some(_))) {
+                let _ = devices.plot.send(line);
+            } else if let Some(reply) = shell::run_line(&mut app, &line) {
+                println!("{reply}");
+            }
+        }
+        if let Err(error) = devices
+            .settings
+            .update(Settings::from_app(&app), Instant::now())
+        {
+            eprintln!("Can't save the settings: {error}");
+        }
+        let clock = app.context().clock;
+        if clock != saved_clock {
+            clock::save(&clock);
+            saved_clock = clock;
+        }
+
+        let power = &app.context().power;
+        if let Err(error) = devices.backlight.lock().unwrap().set(power.backlight()) {
+            eprintln!("Can't dim the backlight: {error}");
+        }
+        let sleep = match power.state() {
+            PowerState::Sleep => Some(power.config.sleep_mode),
+            // Settings are saved below, so a safe shutdown is a deep sleep until S1.
+            PowerState::Shutdown => Some(SleepMode::Deep),
+            PowerState::Active | PowerState::Dimmed | PowerState::ScreenOff => None,
+        };
+        if sleep.is_some() {
+            if let Err(error) = devices.settings.flush() {
+                eprintln!("Can't save the settings: {error}");
+            }
+        }
+        match sleep {
+            Some(SleepMode::Light) => {
+                light_sleep(board.pins.button);
+                app.context_mut().power.woke_up(Instant::now());
+            }
+            Some(SleepMode::Deep) => deep_sleep(board.pins.button),
+            None => {}
+        }
+    }
+}
+
+/// Sleeps until S1 pulls its pin low, then restores the button interrupt.
+fn light_sleep(button: u8) {
+    let pin = i32::from(button);
+    unsafe {
+        esp!(sys::gpio_wakeup_enable(
+            pin,
+            sys::gpio_int_type_t_GPIO_INTR_LOW_LEVEL
+        ))
+        .unwrap();
+        esp!(sys::esp_sleep_enable_gpio_wakeup()).unwrap();
+        esp!(sys::esp_light_sleep_start()).unwrap();
+        esp!(sys::gpio_wakeup_disable(pin)).unwrap();
+        esp!(sys::gpio_set_intr_type(
+            pin,
+            sys::gpio_int_type_t_GPIO_INTR_ANYEDGE
+        ))
+        .unwrap();
+    }
+}
+
+/// Powers down until S1 is pressed, which reboots the board.
+fn deep_sleep(button: u8) -> ! {
+    unsafe {
+        esp!(sys::esp_sleep_enable_ext0_wakeup(i32::from(button), 0)).unwrap();
+        sys::esp_deep_sleep_start()
+    }
+}
+
+/// Pin numbers come from the validated board profile, so no pin is handed out twice.
+fn output_pin(pin: u8) -> AnyOutputPin {
+    unsafe { AnyOutputPin::new(pin.into()) }
+}
+
+/// Returns a reader for the voltage at the battery ADC pin in millivolts.
+fn battery_reader(adc: ADC1, pins: Pins, battery: Option<BatterySense>) -> BatteryReader {
+    let Some(battery) = battery else {
+        return Box::new(|| None);
+    };
+    let adc = AdcDriver::new(adc).unwrap();
+    match battery.adc_pin {
+        32 => adc_channel(adc, pins.gpio32, battery.attenuation),
+        33 => adc_channel(adc, pins.gpio33, battery.attenuation),
+        34 => adc_channel(adc, pins.gpio34, battery.attenuation),
+        35 => adc_channel(adc, pins.gpio35, battery.attenuation),
+        36 => adc_channel(adc, pins.gpio36, battery.attenuation),
+        37 => adc_channel(adc, pins.gpio37, battery.attenuation),
+        38 => adc_channel(adc, pins.gpio38, battery.attenuation),
+        39 => adc_channel(adc, pins.gpio39, battery.attenuation),
+        pin => unreachable!("GPIO{pin} is not an ADC1 pin"),
+    }
+}
+
+fn adc_channel<P>(
+    adc: AdcDriver<'static, ADC1>,
+    pin: P,
+    attenuation: AdcAttenuation,
+) -> BatteryReader
+where
+    P: ADCPin<Adc = ADC1> + 'static,
+{
+    // The eFuse line fitting corrects the ADC itself, the per-device calibration the rest.
+    let config = AdcChannelConfig {
+        attenuation: adc_attenuation(attenuation),
+        calibration: AdcCalibration::Line,
+        resolution: Resolution::Resolution12Bit,
+    };
+    let mut channel = AdcChannelDriver::new(adc, pin, &config).unwrap();
+    Box::new(move || channel.read().ok())
+}
+
+fn adc_attenuation(level: AdcAttenuation) -> adc_atten_t {
+    match level {
+        AdcAttenuation::Db0 => attenuation::NONE,
+        AdcAttenuation::Db2_5 => attenuation::DB_2_5,
+        AdcAttenuation::Db6 => attenuation::DB_6,
+        AdcAttenuation::Db11 => attenuation::DB_11,
+    }
+}
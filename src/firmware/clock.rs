@@ -0,0 +1,44 @@
+use esp_idf_svc::sys;
+use mousefood_esp32_demo::clock::{Clock, TimeSource, build_time};
+use std::sync::atomic::{AtomicI32, AtomicU32, Ordering};
+use std::time::{Instant, SystemTime};
+use time::UtcOffset;
+
+/// Marks the RTC memory below as written by us rather than left over from power-on.
+const RTC_MAGIC: u32 = 0x7153_c10c;
+
+// Not initialized on boot, so these survive deep sleep and software resets.
+#[unsafe(link_section = ".rtc_noinit")]
+static RTC_CLOCK_MAGIC: AtomicU32 = AtomicU32::new(0);
+#[unsafe(link_section = ".rtc_noinit")]
+static RTC_UTC_OFFSET: AtomicI32 = AtomicI32::new(0);
+
+/// Sets the clock from the system time kept by the RTC if it was set before the
+/// last reset, otherwise from the build time.
+pub fn restore(clock: &mut Clock) {
+    let now = Instant::now();
+    if RTC_CLOCK_MAGIC.load(Ordering::Relaxed) == RTC_MAGIC {
+        clock.set(SystemTime::now().into(), TimeSource::RtcMemory, now);
+        let offset = UtcOffset::from_whole_seconds(RTC_UTC_OFFSET.load(Ordering::Relaxed));
+        clock.set_offset(offset.unwrap_or(UtcOffset::UTC));
+    } else if let Some(build_time) = build_time() {
+        clock.set(build_time, TimeSource::CompileTime, now);
+    }
+}
+
+/// Hands a clock set over serial to the RTC, which keeps it across resets.
+pub fn save(clock: &Clock) {
+    if clock.source() < TimeSource::RtcMemory {
+        return;
+    }
+    let Some(utc) = clock.utc_at(Instant::now()) else {
+        return;
+    };
+    let time = sys::timeval {
+        tv_sec: utc.unix_timestamp() as _,
+        tv_usec: utc.microsecond() as _,
+    };
+    unsafe { sys::settimeofday(&time, std::ptr::null()) };
+    RTC_UTC_OFFSET.store(clock.offset().whole_seconds(), Ordering::Relaxed);
+    RTC_CLOCK_MAGIC.store(RTC_MAGIC, Ordering::Relaxed);
+}
@@ -0,0 +1,144 @@
+use super::Backlight;
+use embedded_graphics::Pixel;
+use embedded_graphics::pixelcolor::Rgb565;
+use embedded_graphics::prelude::{Dimensions, DrawTarget};
+use embedded_graphics::primitives::Rectangle;
+use esp_idf_svc::sys;
+use mousefood::prelude::*;
+use mousefood_esp32_demo::crash::{CrashReport, CrashScreen};
+use std::panic;
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+/// Marks the report below as written by us rather than left over from power-on.
+const RTC_MAGIC: u32 = 0x7153_0bad;
+/// Longest report kept, the rest of the message is cut off.
+const MAX_REPORT_LEN: usize = 512;
+
+// Not initialized on boot, so the report survives the reset after the panic.
+#[unsafe(link_section = ".rtc_noinit")]
+static RTC_REPORT_MAGIC: AtomicU32 = AtomicU32::new(0);
+#[unsafe(link_section = ".rtc_noinit")]
+static RTC_REPORT_LEN: AtomicUsize = AtomicUsize::new(0);
+#[unsafe(link_section = ".rtc_noinit")]
+static mut RTC_REPORT: [u8; MAX_REPORT_LEN] = [0; MAX_REPORT_LEN];
+
+/// A display shared by the run loop and the panic hook, locked for each draw.
+pub struct SharedDisplay<D> {
+    display: Arc<Mutex<D>>,
+    bounding_box: Rectangle,
+}
+
+impl<D: DrawTarget> SharedDisplay<D> {
+    pub fn new(display: D) -> Self {
+        Self {
+            bounding_box: display.bounding_box(),
+            display: Arc::new(Mutex::new(display)),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, D> {
+        self.display.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<D> Clone for SharedDisplay<D> {
+    fn clone(&self) -> Self {
+        Self {
+            display: Arc::clone(&self.display),
+            bounding_box: self.bounding_box,
+        }
+    }
+}
+
+impl<D> Dimensions for SharedDisplay<D> {
+    fn bounding_box(&self) -> Rectangle {
+        self.bounding_box
+    }
+}
+
+impl<D: DrawTarget> DrawTarget for SharedDisplay<D> {
+    type Color = D::Color;
+    type Error = D::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        self.lock().draw_iter(pixels)
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        self.lock().fill_contiguous(area, colors)
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        self.lock().fill_solid(area, color)
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.lock().clear(color)
+    }
+}
+
+/// Installs a panic hook that logs the panic, keeps a [`CrashReport`] in RTC memory
+/// for [`take_report`] and draws it on `display` with the backlight at full.
+///
+/// Nothing is drawn if the panic happened while the display or the backlight was
+/// in use, e.g. halfway through a frame.
+pub fn install_hook<D>(display: SharedDisplay<D>, backlight: Arc<Mutex<Backlight>>)
+where
+    D: DrawTarget<Color = Rgb565> + Send + 'static,
+{
+    let log = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        // Log first, in case drawing panics again.
+        log(info);
+        let uptime = Duration::from_micros(unsafe { sys::esp_timer_get_time() } as u64);
+        let report = CrashReport::from_panic(info, uptime);
+        save(&report);
+        let (Ok(mut backlight), Ok(mut display)) =
+            (backlight.try_lock(), display.display.try_lock())
+        else {
+            return;
+        };
+        // Still worth drawing if this fails, the screen may be lit already.
+        let _ = backlight.set(100);
+        draw(&mut *display, &report);
+    }));
+}
+
+/// Returns the report of a panic before the last reset, once.
+pub fn take_report() -> Option<CrashReport> {
+    if RTC_REPORT_MAGIC.swap(0, Ordering::Relaxed) != RTC_MAGIC {
+        return None;
+    }
+    let len = RTC_REPORT_LEN.load(Ordering::Relaxed).min(MAX_REPORT_LEN);
+    let record = unsafe { std::slice::from_raw_parts((&raw const RTC_REPORT).cast::<u8>(), len) };
+    CrashReport::from_bytes(record)
+}
+
+fn save(report: &CrashReport) {
+    let record = report.to_bytes(MAX_REPORT_LEN);
+    unsafe {
+        (&raw mut RTC_REPORT)
+            .cast::<u8>()
+            .copy_from_nonoverlapping(record.as_ptr(), record.len());
+    }
+    RTC_REPORT_LEN.store(record.len(), Ordering::Relaxed);
+    RTC_REPORT_MAGIC.store(RTC_MAGIC, Ordering::Relaxed);
+}
+
+fn draw<D>(display: &mut D, report: &CrashReport)
+where
+    D: DrawTarget<Color = Rgb565> + 'static,
+{
+    let backend = EmbeddedBackend::new(display, Default::default());
+    if let Ok(mut terminal) = Terminal::new(backend) {
+        let _ = terminal.draw(|frame| frame.render_widget(CrashScreen::new(report), frame.area()));
+    }
+}
@@ -0,0 +1,95 @@
+use esp_idf_svc::hal::cpu::CORES;
+use esp_idf_svc::sys;
+use mousefood_esp32_demo::metrics::{
+    CpuLoad, MemoryStats, MetricsSource, ResetReason, SystemMetrics, TaskInfo,
+};
+use std::ffi::CStr;
+use std::time::Duration;
+
+/// Reads heap, task and run time statistics from ESP-IDF and FreeRTOS.
+///
+/// Needs the trace facility and run time stats enabled in `sdkconfig.defaults`.
+#[derive(Default)]
+pub struct EspMetrics {
+    cpu_load: CpuLoad,
+}
+
+impl MetricsSource for EspMetrics {
+    fn sample(&mut self) -> SystemMetrics {
+        let (tasks, total_runtime, idle_runtime) = task_stats();
+        SystemMetrics {
+            heap: memory_stats(sys::MALLOC_CAP_8BIT),
+            psram: Some(memory_stats(sys::MALLOC_CAP_SPIRAM)).filter(|psram| psram.total > 0),
+            uptime: Duration::from_micros(unsafe { sys::esp_timer_get_time() } as u64),
+            reset_reason: reset_reason(),
+            tasks,
+            cpu_load: self.cpu_load.update(total_runtime, idle_runtime),
+        }
+    }
+
+    fn reset(&mut self) {
+        self.cpu_load = CpuLoad::default();
+    }
+}
+
+fn memory_stats(caps: u32) -> MemoryStats {
+    unsafe {
+        MemoryStats {
+            total: sys::heap_caps_get_total_size(caps),
+            free: sys::heap_caps_get_free_size(caps),
+            min_free: sys::heap_caps_get_minimum_free_size(caps),
+            largest_block: sys::heap_caps_get_largest_free_block(caps),
+        }
+    }
+}
+
+fn reset_reason() -> ResetReason {
+    #[allow(non_upper_case_globals)]
+    match unsafe { sys::esp_reset_reason() } {
+        sys::esp_reset_reason_t_ESP_RST_POWERON => ResetReason::PowerOn,
+        sys::esp_reset_reason_t_ESP_RST_EXT => ResetReason::External,
+        sys::esp_reset_reason_t_ESP_RST_SW => ResetReason::Software,
+        sys::esp_reset_reason_t_ESP_RST_PANIC => ResetReason::Panic,
+        sys::esp_reset_reason_t_ESP_RST_INT_WDT
+        | sys::esp_reset_reason_t_ESP_RST_TASK_WDT
+        | sys::esp_reset_reason_t_ESP_RST_WDT => ResetReason::Watchdog,
+        sys::esp_reset_reason_t_ESP_RST_DEEPSLEEP => ResetReason::DeepSleep,
+        sys::esp_reset_reason_t_ESP_RST_BROWNOUT => ResetReason::Brownout,
+        _ => ResetReason::Unknown,
+    }
+}
+
+/// Returns every task, the total run time and the run time of each core's idle task.
+fn task_stats() -> (Vec<TaskInfo>, u64, Vec<u64>) {
+    // Leave room for tasks started between the two calls.
+    let capacity = unsafe { sys::uxTaskGetNumberOfTasks() } + 4;
+    let mut statuses: Vec<sys::TaskStatus_t> = Vec::with_capacity(capacity as usize);
+    let mut total_runtime = 0;
+    unsafe {
+        let count = sys::uxTaskGetSystemState(statuses.as_mut_ptr(), capacity, &mut total_runtime);
+        statuses.set_len(count as usize);
+    }
+
+    let idle_handles: Vec<sys::TaskHandle_t> = (0..CORES)
+        .map(|core| unsafe { sys::xTaskGetIdleTaskHandleForCore(core as i32) })
+        .collect();
+    let mut idle_runtime = vec![0; idle_handles.len()];
+    let tasks = statuses
+        .iter()
+        .map(|status| {
+            if let Some(core) = idle_handles.iter().position(|&idle| idle == status.xHandle) {
+                idle_runtime[core] = u64::from(status.ulRunTimeCounter);
+            }
+            TaskInfo {
+                name: unsafe { CStr::from_ptr(status.pcTaskName) }
+                    .to_string_lossy()
+                    .into_owned(),
+                stack_high_water: status.usStackHighWaterMark as usize,
+                core: u8::try_from(status.xCoreID)
+                    .ok()
+                    .filter(|&core| u32::from(core) < CORES),
+            }
+        })
+        .collect();
+    (tasks, u64::from(total_runtime), idle_runtime)
+}
@@ -0,0 +1,36 @@
+use esp_idf_svc::sys::{self, esp};
+use std::io::{self, BufRead};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+/// Reads lines typed on the serial console in the background.
+pub fn console_lines() -> Receiver<String> {
+    let uart = sys::CONFIG_ESP_CONSOLE_UART_NUM as i32;
+    // Without the driver, reading stdin doesn't block and returns nothing.
+    unsafe {
+        esp!(sys::uart_driver_install(
+            uart,
+            256,
+            0,
+            0,
+            std::ptr::null_mut(),
+            0
+        ))
+        .unwrap();
+        sys::esp_vfs_dev_uart_use_driver(uart);
+    }
+
+    let (sender, receiver) = mpsc::channel();
+    thread::Builder::new()
+        .name("console".into())
+        .stack_size(4096)
+        .spawn(move || {
+            for line in io::stdin().lock().lines().map_while(Result::ok) {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        })
+        .unwrap();
+    receiver
+}
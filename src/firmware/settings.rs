@@ -0,0 +1,57 @@
+use esp_idf_svc::nvs::{EspDefaultNvsPartition, EspNvs, NvsDefault};
+use esp_idf_svc::sys::EspError;
+use mousefood_esp32_demo::settings::SettingsBackend;
+use std::io;
+
+const NAMESPACE: &str = "demo";
+const SETTINGS_KEY: &str = "settings";
+/// Where firmware before the settings store kept the battery calibration.
+const LEGACY_NAMESPACE: &str = "battery";
+const LEGACY_CALIBRATION_KEY: &str = "calibration";
+/// Longest record that can be read back, far more than settings take.
+const MAX_RECORD_LEN: usize = 2048;
+
+/// Keeps the settings record in the default NVS partition.
+pub struct NvsBackend {
+    nvs: EspNvs<NvsDefault>,
+    legacy: EspNvs<NvsDefault>,
+}
+
+impl NvsBackend {
+    pub fn new(partition: EspDefaultNvsPartition) -> Result<Self, EspError> {
+        Ok(Self {
+            nvs: EspNvs::new(partition.clone(), NAMESPACE, true)?,
+            legacy: EspNvs::new(partition, LEGACY_NAMESPACE, true)?,
+        })
+    }
+}
+
+impl SettingsBackend for NvsBackend {
+    fn read(&mut self) -> io::Result<Option<Vec<u8>>> {
+        let mut buffer = vec![0; MAX_RECORD_LEN];
+        let record = self
+            .nvs
+            .get_blob(SETTINGS_KEY, &mut buffer)
+            .map_err(io::Error::other)?;
+        if let Some(record) = record {
+            return Ok(Some(record.to_vec()));
+        }
+        // Settings read the old calibration blob as a version 0 record.
+        let legacy = self
+            .legacy
+            .get_blob(LEGACY_CALIBRATION_KEY, &mut buffer)
+            .map_err(io::Error::other)?;
+        Ok(legacy.map(<[u8]>::to_vec))
+    }
+
+    fn write(&mut self, record: &[u8]) -> io::Result<()> {
+        self.nvs
+            .set_blob(SETTINGS_KEY, record)
+            .map_err(io::Error::other)?;
+        // The calibration is part of the settings now.
+        self.legacy
+            .remove(LEGACY_CALIBRATION_KEY)
+            .map_err(io::Error::other)?;
+        Ok(())
+    }
+}
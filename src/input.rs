@@ -0,0 +1,32 @@
+mod button;
+mod chord;
+#[cfg(target_os = "espidf")]
+mod gpio;
+mod scripted;
+
+pub use button::{ButtonDetector, ButtonTimings};
+pub use chord::ChordInput;
+#[cfg(target_os = "espidf")]
+pub use gpio::GpioButton;
+pub use scripted::ScriptedInput;
+
+use std::time::Duration;
+
+/// Input delivered to the active screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputEvent {
+    /// S1 was pressed and released once.
+    ShortPress,
+    /// S1 has been held down past the long press threshold.
+    LongPress,
+    /// S1 was pressed twice in quick succession.
+    DoublePress,
+    /// S1 is still held after a long press.
+    Repeat,
+}
+
+/// Anything the run loop can wait on for input events.
+pub trait InputSource {
+    /// Blocks for at most `timeout` until the next event arrives.
+    fn wait(&mut self, timeout: Duration) -> Option<InputEvent>;
+}
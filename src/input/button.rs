@@ -0,0 +1,245 @@
+use super::InputEvent;
+use std::time::{Duration, Instant};
+
+/// Timings used to turn raw button levels into [`InputEvent`]s.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ButtonTimings {
+    /// How long a level must be stable before it is accepted.
+    pub debounce: Duration,
+    /// Hold time after which a press becomes a long press.
+    pub long_press: Duration,
+    /// Maximum gap between two presses of a double press.
+    /// A short press is only reported once this window has passed.
+    pub double_press: Duration,
+    /// Interval between repeats while the button stays held after a long press.
+    pub repeat_interval: Duration,
+}
+
+impl Default for ButtonTimings {
+    fn default() -> Self {
+        Self {
+            debounce: Duration::from_millis(30),
+            long_press: Duration::from_millis(600),
+            double_press: Duration::from_millis(300),
+            repeat_interval: Duration::from_millis(200),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Idle,
+    /// Pressed, not held long enough for a long press yet.
+    Down {
+        since: Instant,
+    },
+    /// Long press reported, repeating while held.
+    Held {
+        next_repeat: Instant,
+    },
+    /// Released after a short press, waiting for a possible second press.
+    Up {
+        since: Instant,
+    },
+    /// Second press of a double press, ignored until released.
+    WaitRelease,
+}
+
+/// Debounced state machine detecting short, long and double presses of a single button.
+///
+/// It is fed the raw button level together with the current time, so it can be
+/// driven by a GPIO interrupt on the board or by a fake clock in tests.
+#[derive(Debug, Clone)]
+pub struct ButtonDetector {
+    timings: ButtonTimings,
+    raw: bool,
+    raw_since: Instant,
+    stable: bool,
+    state: State,
+}
+
+impl ButtonDetector {
+    pub fn new(timings: ButtonTimings) -> Self {
+        Self {
+            timings,
+            raw: false,
+            raw_since: Instant::now(),
+            stable: false,
+            state: State::Idle,
+        }
+    }
+
+    /// Feeds the raw level (`true` while pressed) sampled at `now`.
+    pub fn update(&mut self, pressed: bool, now: Instant) -> Option<InputEvent> {
+        if pressed != self.raw {
+            self.raw = pressed;
+            self.raw_since = now;
+        }
+        let settled = now.duration_since(self.raw_since) >= self.timings.debounce;
+        let edge = if self.raw != self.stable && settled {
+            self.stable = self.raw;
+            Some(self.stable)
+        } else {
+            None
+        };
+        let at = self.raw_since;
+
+        let (state, event) = match (self.state, edge) {
+            (State::Idle, Some(true)) => (State::Down { since: at }, None),
+            (State::Down { .. }, Some(false)) if self.timings.double_press.is_zero() => {
+                (State::Idle, Some(InputEvent::ShortPress))
+            }
+            (State::Down { .. }, Some(false)) => (State::Up { since: at }, None),
+            (State::Down { since }, None) if now >= since + self.timings.long_press => {
+                let next_repeat = since + self.timings.long_press + self.timings.repeat_interval;
+                (State::Held { next_repeat }, Some(InputEvent::LongPress))
+            }
+            (State::Held { .. }, Some(false)) => (State::Idle, None),
+            (State::Held { next_repeat }, None) if now >= next_repeat => {
+                let next_repeat = next_repeat + self.timings.repeat_interval;
+                (State::Held { next_repeat }, Some(InputEvent::Repeat))
+            }
+            (State::Up { .. }, Some(true)) => (State::WaitRelease, Some(InputEvent::DoublePress)),
+            (State::Up { since }, None) if now >= since + self.timings.double_press => {
+                (State::Idle, Some(InputEvent::ShortPress))
+            }
+            (State::WaitRelease, Some(false)) => (State::Idle, None),
+            (state, _) => (state, None),
+        };
+        self.state = state;
+        event
+    }
+
+    /// The next time [`update`](Self::update) must be called even if the level doesn't change.
+    pub fn next_deadline(&self) -> Option<Instant> {
+        let debounce = (self.raw != self.stable).then(|| self.raw_since + self.timings.debounce);
+        let timeout = match self.state {
+            State::Down { since } => Some(since + self.timings.long_press),
+            State::Held { next_repeat } => Some(next_repeat),
+            State::Up { since } => Some(since + self.timings.double_press),
+            State::Idle | State::WaitRelease => None,
+        };
+        debounce.into_iter().chain(timeout).min()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Drives a detector with a fake clock, sampling every millisecond.
+    struct FakeButton {
+        detector: ButtonDetector,
+        start: Instant,
+        now: Duration,
+    }
+
+    impl FakeButton {
+        fn new() -> Self {
+            Self {
+                detector: ButtonDetector::new(ButtonTimings::default()),
+                start: Instant::now(),
+                now: Duration::ZERO,
+            }
+        }
+
+        fn hold(&mut self, pressed: bool, millis: u64) -> Vec<(u64, InputEvent)> {
+            let mut events = Vec::new();
+            for _ in 0..millis {
+                let event = self.detector.update(pressed, self.start + self.now);
+                if let Some(event) = event {
+                    events.push((self.now.as_millis() as u64, event));
+                }
+                self.now += Duration::from_millis(1);
+            }
+            events
+        }
+    }
+
+    fn kinds(events: &[(u64, InputEvent)]) -> Vec<InputEvent> {
+        events.iter().map(|&(_, event)| event).collect()
+    }
+
+    #[test]
+    fn short_press_after_double_press_window() {
+        let mut button = FakeButton::new();
+        assert!(button.hold(true, 100).is_empty());
+        assert_eq!(button.hold(false, 500), [(400, InputEvent::ShortPress)]);
+    }
+
+    #[test]
+    fn ignores_bounces_shorter_than_debounce() {
+        let mut button = FakeButton::new();
+        for _ in 0..5 {
+            button.hold(true, 5);
+            button.hold(false, 5);
+        }
+        assert!(button.hold(false, 1000).is_empty());
+    }
+
+    #[test]
+    fn bouncy_press_is_a_single_short_press() {
+        let mut button = FakeButton::new();
+        let mut events = Vec::new();
+        for _ in 0..3 {
+            events.extend(button.hold(true, 3));
+            events.extend(button.hold(false, 2));
+        }
+        events.extend(button.hold(true, 100));
+        events.extend(button.hold(false, 500));
+        assert_eq!(kinds(&events), [InputEvent::ShortPress]);
+    }
+
+    #[test]
+    fn double_press() {
+        let mut button = FakeButton::new();
+        let mut events = button.hold(true, 80);
+        events.extend(button.hold(false, 150));
+        events.extend(button.hold(true, 80));
+        events.extend(button.hold(false, 500));
+        assert_eq!(kinds(&events), [InputEvent::DoublePress]);
+    }
+
+    #[test]
+    fn long_press_then_repeats_until_release() {
+        let mut button = FakeButton::new();
+        let events = button.hold(true, 1050);
+        assert_eq!(
+            events,
+            [
+                (600, InputEvent::LongPress),
+                (800, InputEvent::Repeat),
+                (1000, InputEvent::Repeat),
+            ]
+        );
+        assert!(button.hold(false, 1000).is_empty());
+    }
+
+    #[test]
+    fn custom_timings() {
+        let mut button = FakeButton::new();
+        button.detector = ButtonDetector::new(ButtonTimings {
+            double_press: Duration::ZERO,
+            ..ButtonTimings::default()
+        });
+        button.hold(true, 100);
+        assert_eq!(button.hold(false, 100), [(130, InputEvent::ShortPress)]);
+    }
+
+    #[test]
+    fn deadline_follows_pending_timeouts() {
+        let start = Instant::now();
+        let mut detector = ButtonDetector::new(ButtonTimings::default());
+        assert_eq!(detector.next_deadline(), None);
+        detector.update(true, start);
+        assert_eq!(
+            detector.next_deadline(),
+            Some(start + Duration::from_millis(30))
+        );
+        detector.update(true, start + Duration::from_millis(30));
+        assert_eq!(
+            detector.next_deadline(),
+            Some(start + Duration::from_millis(600))
+        );
+    }
+}
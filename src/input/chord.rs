@@ -0,0 +1,70 @@
+use super::{InputEvent, InputSource};
+use std::time::Duration;
+
+/// Turns a long press of S1 while a second button is held into a chord.
+///
+/// The chord's long press and the repeats that follow it are swallowed,
+/// so the active screen never sees them.
+pub struct ChordInput<S, F> {
+    source: S,
+    held: F,
+    triggered: bool,
+}
+
+impl<S: InputSource, F: FnMut() -> bool> ChordInput<S, F> {
+    /// `held` reports whether the second button is down right now.
+    pub fn new(source: S, held: F) -> Self {
+        Self {
+            source,
+            held,
+            triggered: false,
+        }
+    }
+
+    /// Returns `true` once for every chord since the last call.
+    pub fn take_triggered(&mut self) -> bool {
+        std::mem::take(&mut self.triggered)
+    }
+}
+
+impl<S: InputSource, F: FnMut() -> bool> InputSource for ChordInput<S, F> {
+    fn wait(&mut self, timeout: Duration) -> Option<InputEvent> {
+        match self.source.wait(timeout)? {
+            InputEvent::LongPress if (self.held)() => {
+                self.triggered = true;
+                None
+            }
+            InputEvent::Repeat if (self.held)() => None,
+            event => Some(event),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::ScriptedInput;
+    use std::cell::Cell;
+
+    #[test]
+    fn swallows_long_press_while_held() {
+        let held = Cell::new(false);
+        let script = ScriptedInput::from_iter([
+            InputEvent::LongPress,
+            InputEvent::LongPress,
+            InputEvent::Repeat,
+            InputEvent::ShortPress,
+        ]);
+        let mut input = ChordInput::new(script, || held.get());
+        let wait = Duration::ZERO;
+
+        assert_eq!(input.wait(wait), Some(InputEvent::LongPress));
+        assert!(!input.take_triggered());
+        held.set(true);
+        assert_eq!(input.wait(wait), None);
+        assert_eq!(input.wait(wait), None);
+        assert_eq!(input.wait(wait), Some(InputEvent::ShortPress));
+        assert!(input.take_triggered());
+        assert!(!input.take_triggered());
+    }
+}
@@ -0,0 +1,59 @@
+use super::{ButtonDetector, ButtonTimings, InputEvent, InputSource};
+use esp_idf_svc::hal::delay::TickType;
+use esp_idf_svc::hal::gpio::{AnyInputPin, Input, InterruptType, PinDriver};
+use esp_idf_svc::hal::task::notification::Notification;
+use esp_idf_svc::sys::EspError;
+use std::num::NonZeroU32;
+use std::time::{Duration, Instant};
+
+/// An active-low button such as S1, sampled on every edge interrupt.
+pub struct GpioButton {
+    pin: PinDriver<'static, AnyInputPin, Input>,
+    notification: Notification,
+    detector: ButtonDetector,
+}
+
+impl GpioButton {
+    pub fn new(pin: AnyInputPin, timings: ButtonTimings) -> Result<Self, EspError> {
+        let mut pin = PinDriver::input(pin)?;
+        pin.set_interrupt_type(InterruptType::AnyEdge)?;
+        let notification = Notification::new();
+        let notifier = notification.notifier();
+        unsafe {
+            pin.subscribe(move || {
+                notifier.notify_and_yield(NonZeroU32::new(1).unwrap());
+            })?;
+        }
+        pin.enable_interrupt()?;
+        Ok(Self {
+            pin,
+            notification,
+            detector: ButtonDetector::new(timings),
+        })
+    }
+}
+
+impl InputSource for GpioButton {
+    fn wait(&mut self, timeout: Duration) -> Option<InputEvent> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let now = Instant::now();
+            if let Some(event) = self.detector.update(self.pin.is_low(), now) {
+                return Some(event);
+            }
+            if now >= deadline {
+                return None;
+            }
+            // Wake up on the next edge, or when the detector has a timeout to check.
+            let wake = self
+                .detector
+                .next_deadline()
+                .map_or(deadline, |next| next.min(deadline));
+            let ticks = TickType::from(wake.saturating_duration_since(now)).ticks();
+            if self.notification.wait(ticks).is_some() {
+                // The interrupt is disabled after every trigger, so re-arm it right away.
+                self.pin.enable_interrupt().unwrap();
+            }
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use super::{InputEvent, InputSource};
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// In-memory input that replays a fixed script, one step per wait.
+///
+/// Waits never block, so a script runs as fast as it is consumed.
+/// Useful for driving screens on the host without any hardware.
+#[derive(Debug, Default, Clone)]
+pub struct ScriptedInput {
+    steps: VecDeque<Option<InputEvent>>,
+}
+
+impl ScriptedInput {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues an event for the next free wait.
+    pub fn push(&mut self, event: InputEvent) -> &mut Self {
+        self.steps.push_back(Some(event));
+        self
+    }
+
+    /// Queues `waits` waits that time out without an event.
+    pub fn idle(&mut self, waits: usize) -> &mut Self {
+        self.steps.extend(std::iter::repeat_n(None, waits));
+        self
+    }
+
+    /// Returns `true` once every scripted step has been consumed.
+    pub fn is_finished(&self) -> bool {
+        self.steps.is_empty()
+    }
+}
+
+impl FromIterator<InputEvent> for ScriptedInput {
+    fn from_iter<I: IntoIterator<Item = InputEvent>>(iter: I) -> Self {
+        Self {
+            steps: iter.into_iter().map(Some).collect(),
+        }
+    }
+}
+
+impl InputSource for ScriptedInput {
+    fn wait(&mut self, _timeout: Duration) -> Option<InputEvent> {
+        self.steps.pop_front().flatten()
+    }
+}
@@ -0,0 +1,31 @@
+pub mod app;
+pub mod axis;
+pub mod battery;
+pub mod battery_history;
+pub mod board;
+pub mod calibration;
+pub mod carousel;
+pub mod chart;
+pub mod clock;
+pub mod crash;
+pub mod gauge;
+pub mod helpers;
+pub mod input;
+pub mod lorem;
+pub mod metrics;
+pub mod overlay;
+pub mod power;
+pub mod ratatui_logo;
+pub mod scheduler;
+pub mod screen;
+pub mod screenshot;
+pub mod settings;
+pub mod settings_screen;
+pub mod shell;
+pub mod source;
+pub mod status_bar;
+pub mod system_monitor;
+pub mod tabs;
+pub mod theme;
+pub mod transition;
+pub mod voltage;
@@ -0,0 +1,202 @@
+use std::fmt;
+use std::time::Duration;
+
+/// Reads the system metrics shown by the
+/// [`SystemMonitorApp`](crate::system_monitor::SystemMonitorApp).
+pub trait MetricsSource {
+    /// Takes a new sample. CPU load is measured since the previous call.
+    fn sample(&mut self) -> SystemMetrics;
+
+    /// Starts the CPU load over, e.g. when the screen was away for a while.
+    fn reset(&mut self) {}
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SystemMetrics {
+    pub heap: MemoryStats,
+    /// `None` on boards without PSRAM.
+    pub psram: Option<MemoryStats>,
+    pub uptime: Duration,
+    pub reset_reason: ResetReason,
+    pub tasks: Vec<TaskInfo>,
+    /// Load of each core in percent, empty until two samples were taken.
+    pub cpu_load: Vec<u8>,
+}
+
+impl SystemMetrics {
+    /// What an ESP32 running the demo typically reports, for hosts that have no
+    /// FreeRTOS to ask and for tests.
+    pub fn typical() -> Self {
+        let task = |name: &str, stack_high_water, core| TaskInfo {
+            name: name.to_string(),
+            stack_high_water,
+            core,
+        };
+        Self {
+            heap: MemoryStats {
+                total: 300_000,
+                free: 183_500,
+                min_free: 171_200,
+                largest_block: 110_580,
+            },
+            psram: None,
+            uptime: Duration::ZERO,
+            reset_reason: ResetReason::PowerOn,
+            tasks: vec![
+                task("main", 3_120, Some(0)),
+                task("IDLE0", 1_000, Some(0)),
+                task("ipc0", 428, Some(0)),
+                task("esp_timer", 2_408, None),
+            ],
+            cpu_load: vec![24, 2],
+        }
+    }
+}
+
+/// Sizes of a heap region in bytes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryStats {
+    pub total: usize,
+    pub free: usize,
+    /// Lowest free size since boot.
+    pub min_free: usize,
+    /// Largest block that can still be allocated at once.
+    pub largest_block: usize,
+}
+
+impl MemoryStats {
+    /// Fraction of the region in use, from 0 to 1.
+    pub fn used_ratio(&self) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        let used = self.total.saturating_sub(self.free);
+        used as f64 / self.total as f64
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskInfo {
+    pub name: String,
+    /// Least stack space left since the task started, in bytes.
+    pub stack_high_water: usize,
+    /// Core the task is pinned to, `None` if it may run on either.
+    pub core: Option<u8>,
+}
+
+/// Why the chip last started.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ResetReason {
+    #[default]
+    Unknown,
+    PowerOn,
+    External,
+    Software,
+    Panic,
+    Watchdog,
+    DeepSleep,
+    Brownout,
+}
+
+impl fmt::Display for ResetReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let reason = match self {
+            ResetReason::Unknown => "unknown",
+            ResetReason::PowerOn => "power-on",
+            ResetReason::External => "reset pin",
+            ResetReason::Software => "software",
+            ResetReason::Panic => "panic",
+            ResetReason::Watchdog => "watchdog",
+            ResetReason::DeepSleep => "deep sleep",
+            ResetReason::Brownout => "brownout",
+        };
+        f.write_str(reason)
+    }
+}
+
+/// Per-core load from the run time counters of the idle tasks.
+///
+/// Both counters only ever grow, so the load is taken from the difference
+/// between two consecutive samples.
+#[derive(Debug, Clone, Default)]
+pub struct CpuLoad {
+    previous: Option<(u64, Vec<u64>)>,
+}
+
+impl CpuLoad {
+    /// Takes the total run time and the idle time of each core, both since boot,
+    /// and returns the load of each core in percent since the last update.
+    pub fn update(&mut self, total: u64, idle: Vec<u64>) -> Vec<u8> {
+        let load = match &self.previous {
+            Some((previous_total, previous_idle)) if total > *previous_total => {
+                let elapsed = (total - previous_total) as f64;
+                idle.iter()
+                    .zip(previous_idle)
+                    .map(|(&idle, &previous)| {
+                        let idle = idle.saturating_sub(previous) as f64 / elapsed;
+                        ((1.0 - idle).clamp(0.0, 1.0) * 100.0).round() as u8
+                    })
+                    .collect()
+            }
+            _ => Vec::new(),
+        };
+        self.previous = Some((total, idle));
+        load
+    }
+}
+
+/// Formats a size in bytes as `512B`, `12.3K` or `4.0M`.
+pub fn format_bytes(bytes: usize) -> String {
+    const KIB: f64 = 1024.0;
+    let bytes_f = bytes as f64;
+    if bytes_f < KIB {
+        format!("{bytes}B")
+    } else if bytes_f < KIB * KIB {
+        format!("{:.1}K", bytes_f / KIB)
+    } else {
+        format!("{:.1}M", bytes_f / (KIB * KIB))
+    }
+}
+
+/// Formats an uptime as `5m12s`, `3h04m` or `2d07h`.
+pub fn format_uptime(uptime: Duration) -> String {
+    let seconds = uptime.as_secs();
+    let (days, hours, minutes) = (seconds / 86400, seconds / 3600 % 24, seconds / 60 % 60);
+    if days > 0 {
+        format!("{days}d{hours:02}h")
+    } else if hours > 0 {
+        format!("{hours}h{minutes:02}m")
+    } else {
+        format!("{minutes}m{:02}s", seconds % 60)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cpu_load_from_idle_counters() {
+        let mut load = CpuLoad::default();
+        assert!(load.update(1_000, vec![900, 1_000]).is_empty());
+        assert_eq!(load.update(2_000, vec![1_650, 1_990]), [25, 1]);
+        // The counters didn't move, so there's nothing to measure.
+        assert!(load.update(2_000, vec![1_650, 1_990]).is_empty());
+    }
+
+    #[test]
+    fn formats_sizes_and_uptime() {
+        assert_eq!(format_bytes(512), "512B");
+        assert_eq!(format_bytes(183_500), "179.2K");
+        assert_eq!(format_bytes(4 * 1024 * 1024), "4.0M");
+        assert_eq!(format_uptime(Duration::from_secs(312)), "5m12s");
+        assert_eq!(
+            format_uptime(Duration::from_secs(3 * 3600 + 4 * 60)),
+            "3h04m"
+        );
+        assert_eq!(
+            format_uptime(Duration::from_secs(2 * 86400 + 7 * 3600)),
+            "2d07h"
+        );
+    }
+}
@@ -0,0 +1,313 @@
+use crate::battery::ChargeState;
+use crate::helpers::center;
+use crate::input::InputEvent;
+use crate::screen::Context;
+use crate::status_bar;
+use mousefood::prelude::*;
+use mousefood::ratatui::widgets::{Block, Clear, Paragraph};
+use std::time::{Duration, Instant};
+
+/// Drawn by the [`App`](crate::app::App) on top of whichever screen is active.
+pub trait Overlay {
+    /// Called once per frame before drawing.
+    fn update(&mut self, _now: Instant, _ctx: &mut Context) {}
+
+    /// Sees every event before the screen. Returns `true` if the event was consumed.
+    fn handle_event(&mut self, _event: InputEvent, _ctx: &mut Context) -> bool {
+        false
+    }
+
+    fn draw(&self, frame: &mut Frame, ctx: &Context);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatteryAlertConfig {
+    pub warning_millivolts: u16,
+    pub critical_millivolts: u16,
+    /// How far the voltage has to recover before a level is left again.
+    pub hysteresis_millivolts: u16,
+    /// How long the warning toast stays up.
+    pub toast_duration: Duration,
+    /// When to repeat the warning toast or the dismissed shutdown prompt.
+    pub reminder_interval: Duration,
+    /// Time to cancel before the board shuts down at critical level.
+    pub shutdown_countdown: Duration,
+}
+
+impl Default for BatteryAlertConfig {
+    fn default() -> Self {
+        Self {
+            warning_millivolts: 3500,
+            critical_millivolts: 3350,
+            hysteresis_millivolts: 50,
+            toast_duration: Duration::from_secs(5),
+            reminder_interval: Duration::from_secs(5 * 60),
+            shutdown_countdown: Duration::from_secs(30),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum BatteryLevel {
+    Normal,
+    Warning,
+    Critical,
+}
+
+/// Warns with a toast when the battery runs low and offers a safe shutdown
+/// in a modal popup once it is critical.
+#[derive(Debug, Clone)]
+pub struct LowBatteryOverlay {
+    config: BatteryAlertConfig,
+    level: BatteryLevel,
+    now: Instant,
+    toast_until: Option<Instant>,
+    next_reminder: Option<Instant>,
+    shutdown_at: Option<Instant>,
+}
+
+impl LowBatteryOverlay {
+    pub fn new(config: BatteryAlertConfig) -> Self {
+        Self {
+            config,
+            level: BatteryLevel::Normal,
+            now: Instant::now(),
+            toast_until: None,
+            next_reminder: None,
+            shutdown_at: None,
+        }
+    }
+
+    pub fn level(&self) -> BatteryLevel {
+        self.level
+    }
+
+    /// Time left before the shutdown, `None` unless the prompt is showing.
+    pub fn shutdown_in(&self) -> Option<Duration> {
+        self.shutdown_at
+            .map(|at| at.saturating_duration_since(self.now))
+    }
+
+    fn toast_visible(&self) -> bool {
+        self.toast_until.is_some_and(|until| self.now < until)
+    }
+
+    fn classify(&self, millivolts: u16) -> BatteryLevel {
+        let config = &self.config;
+        // Leaving a level needs the voltage to recover past the hysteresis.
+        let threshold = |level: BatteryLevel, millivolts: u16| {
+            if self.level >= level {
+                millivolts.saturating_add(config.hysteresis_millivolts)
+            } else {
+                millivolts
+            }
+        };
+        if millivolts < threshold(BatteryLevel::Critical, config.critical_millivolts) {
+            BatteryLevel::Critical
+        } else if millivolts < threshold(BatteryLevel::Warning, config.warning_millivolts) {
+            BatteryLevel::Warning
+        } else {
+            BatteryLevel::Normal
+        }
+    }
+}
+
+impl Default for LowBatteryOverlay {
+    fn default() -> Self {
+        Self::new(BatteryAlertConfig::default())
+    }
+}
+
+impl Overlay for LowBatteryOverlay {
+    fn update(&mut self, now: Instant, ctx: &mut Context) {
+        self.now = now;
+        let Some(state) = ctx.battery.state() else {
+            return;
+        };
+        let level = if state.charge == ChargeState::Charging {
+            BatteryLevel::Normal
+        } else {
+            self.classify(state.millivolts)
+        };
+
+        let reminder_due = self.next_reminder.is_some_and(|at| now >= at);
+        if level != self.level || reminder_due {
+            self.next_reminder = Some(now + self.config.reminder_interval);
+            self.toast_until = None;
+            self.shutdown_at = None;
+            match level {
+                BatteryLevel::Normal => self.next_reminder = None,
+                BatteryLevel::Warning => self.toast_until = Some(now + self.config.toast_duration),
+                BatteryLevel::Critical => {
+                    self.shutdown_at = Some(now + self.config.shutdown_countdown);
+                    // Turn the screen on, so the prompt can be cancelled.
+                    ctx.power.activity(now);
+                }
+            }
+            self.level = level;
+        }
+
+        if self.shutdown_at.is_some_and(|at| now >= at) {
+            self.shutdown_at = None;
+            ctx.power.shut_down();
+        }
+    }
+
+    fn handle_event(&mut self, event: InputEvent, ctx: &mut Context) -> bool {
+        if self.shutdown_at.is_none() {
+            return false;
+        }
+        match event {
+            InputEvent::ShortPress => self.shutdown_at = None,
+            InputEvent::LongPress => {
+                self.shutdown_at = None;
+                ctx.power.shut_down();
+            }
+            InputEvent::DoublePress | InputEvent::Repeat => {}
+        }
+        true
+    }
+
+    fn draw(&self, frame: &mut Frame, ctx: &Context) {
+        let Some(state) = ctx.battery.state() else {
+            return;
+        };
+        let reading = format!(
+            "{}%  {:.2}V",
+            state.percent,
+            f32::from(state.millivolts) / 1000.0
+        );
+
+        if let Some(remaining) = self.shutdown_in() {
+            let style = Style::default().fg(ctx.theme.text).bg(ctx.theme.danger);
+            let area = center(frame.area(), Constraint::Length(28), Constraint::Length(6));
+            let block = Block::bordered()
+                .border_style(style)
+                .title("Battery critical");
+            let text = vec![
+                Line::from(reading),
+                Line::from(format!("Shutting down in {}s", remaining.as_secs())),
+                Line::from(""),
+                Line::from("[S1] cancel  [hold] now"),
+            ];
+            let popup = Paragraph::new(text).centered().block(block).style(style);
+            frame.render_widget(Clear, area);
+            frame.render_widget(popup, area);
+        } else if self.toast_visible() {
+            let style = Style::default()
+                .fg(ctx.theme.background)
+                .bg(ctx.theme.warning);
+            // Above the status bar, so the battery reading there stays visible.
+            let [screen_area, _] = status_bar::layout(frame.area());
+            let [_, bottom] =
+                Layout::vertical([Constraint::Min(0), Constraint::Length(4)]).areas(screen_area);
+            let area = center(bottom, Constraint::Length(24), Constraint::Length(3));
+            let block = Block::bordered().border_style(style).title("Low battery");
+            let toast = Paragraph::new(reading).centered().block(block).style(style);
+            frame.render_widget(Clear, area);
+            frame.render_widget(toast, area);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::power::PowerState;
+
+    /// Feeds a single filtered reading and updates the overlay at `now`.
+    fn reading(overlay: &mut LowBatteryOverlay, ctx: &mut Context, millivolts: u16, now: Instant) {
+        ctx.battery.clear();
+        ctx.battery.push(millivolts, now);
+        overlay.update(now, ctx);
+    }
+
+    fn secs(seconds: u64) -> Duration {
+        Duration::from_secs(seconds)
+    }
+
+    #[test]
+    fn warning_shows_toast_for_a_while() {
+        let start = Instant::now();
+        let mut ctx = Context::default();
+        let mut overlay = LowBatteryOverlay::default();
+        reading(&mut overlay, &mut ctx, 3600, start);
+        assert_eq!(overlay.level(), BatteryLevel::Normal);
+        reading(&mut overlay, &mut ctx, 3480, start + secs(1));
+        assert_eq!(overlay.level(), BatteryLevel::Warning);
+        assert!(overlay.toast_visible());
+        reading(&mut overlay, &mut ctx, 3480, start + secs(7));
+        assert!(!overlay.toast_visible());
+        reading(&mut overlay, &mut ctx, 3480, start + secs(301));
+        assert!(overlay.toast_visible(), "reminder");
+    }
+
+    #[test]
+    fn levels_have_hysteresis() {
+        let start = Instant::now();
+        let mut ctx = Context::default();
+        let mut overlay = LowBatteryOverlay::default();
+        reading(&mut overlay, &mut ctx, 3490, start);
+        reading(&mut overlay, &mut ctx, 3520, start);
+        assert_eq!(overlay.level(), BatteryLevel::Warning);
+        reading(&mut overlay, &mut ctx, 3550, start);
+        assert_eq!(overlay.level(), BatteryLevel::Normal);
+    }
+
+    #[test]
+    fn critical_shuts_down_after_countdown() {
+        let start = Instant::now();
+        let mut ctx = Context::default();
+        let mut overlay = LowBatteryOverlay::default();
+        reading(&mut overlay, &mut ctx, 3300, start);
+        assert_eq!(overlay.shutdown_in(), Some(secs(30)));
+        reading(&mut overlay, &mut ctx, 3300, start + secs(29));
+        assert!(ctx.power.state().screen_on());
+        reading(&mut overlay, &mut ctx, 3300, start + secs(30));
+        assert_eq!(ctx.power.state(), PowerState::Shutdown);
+    }
+
+    #[test]
+    fn critical_wakes_the_screen() {
+        let mut ctx = Context::default();
+        let start = Instant::now();
+        let mut overlay = LowBatteryOverlay::default();
+        let idle = start + ctx.power.config.off_after;
+        ctx.power.update(idle);
+        assert!(!ctx.power.state().screen_on());
+        reading(&mut overlay, &mut ctx, 3300, idle);
+        assert!(ctx.power.state().screen_on());
+        reading(&mut overlay, &mut ctx, 3300, idle + secs(30));
+        assert_eq!(ctx.power.state(), PowerState::Shutdown);
+    }
+
+    #[test]
+    fn shutdown_can_be_cancelled_or_forced() {
+        let start = Instant::now();
+        let mut ctx = Context::default();
+        let mut overlay = LowBatteryOverlay::default();
+        reading(&mut overlay, &mut ctx, 3300, start);
+        assert!(overlay.handle_event(InputEvent::ShortPress, &mut ctx));
+        reading(&mut overlay, &mut ctx, 3300, start + secs(60));
+        assert_eq!(overlay.shutdown_in(), None);
+        assert!(!overlay.handle_event(InputEvent::ShortPress, &mut ctx));
+
+        reading(&mut overlay, &mut ctx, 3300, start + secs(301));
+        assert_eq!(overlay.shutdown_in(), Some(secs(30)), "asks again");
+        assert!(overlay.handle_event(InputEvent::LongPress, &mut ctx));
+        assert_eq!(ctx.power.state(), PowerState::Shutdown);
+    }
+
+    #[test]
+    fn no_alert_while_charging() {
+        let start = Instant::now();
+        let mut ctx = Context::default();
+        let mut overlay = LowBatteryOverlay::default();
+        for second in 0..60 {
+            ctx.battery
+                .push(3300 + second * 2, start + secs(second.into()));
+        }
+        overlay.update(start + secs(60), &mut ctx);
+        assert_eq!(overlay.level(), BatteryLevel::Normal);
+    }
+}
@@ -0,0 +1,228 @@
+use std::time::{Duration, Instant};
+
+/// How the board sleeps once it has been idle for [`PowerConfig::sleep_after`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SleepMode {
+    /// RAM is kept and the demo continues where it left off.
+    Light,
+    /// Only the RTC stays powered and waking up reboots the board.
+    Deep,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PowerConfig {
+    /// Backlight level in percent while in use.
+    pub brightness: u8,
+    /// Backlight level in percent once dimmed.
+    pub dimmed_brightness: u8,
+    /// Idle times after which the screen dims, turns off and the board sleeps.
+    pub dim_after: Duration,
+    pub off_after: Duration,
+    pub sleep_after: Duration,
+    pub sleep_mode: SleepMode,
+}
+
+impl Default for PowerConfig {
+    fn default() -> Self {
+        Self {
+            brightness: 100,
+            dimmed_brightness: 20,
+            dim_after: Duration::from_secs(20),
+            off_after: Duration::from_secs(60),
+            sleep_after: Duration::from_secs(180),
+            sleep_mode: SleepMode::Light,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerState {
+    Active,
+    Dimmed,
+    ScreenOff,
+    /// The board should go to sleep now.
+    Sleep,
+    /// The board should power down until S1 is pressed, regardless of [`SleepMode`].
+    Shutdown,
+}
+
+impl PowerState {
+    pub fn screen_on(self) -> bool {
+        matches!(self, PowerState::Active | PowerState::Dimmed)
+    }
+}
+
+/// Decides backlight level and sleep from the time since the last button press.
+#[derive(Debug, Clone)]
+pub struct IdlePolicy {
+    pub config: PowerConfig,
+    last_activity: Instant,
+    state: PowerState,
+}
+
+impl IdlePolicy {
+    pub fn new(config: PowerConfig, now: Instant) -> Self {
+        Self {
+            config,
+            last_activity: now,
+            state: PowerState::Active,
+        }
+    }
+
+    pub fn state(&self) -> PowerState {
+        self.state
+    }
+
+    /// Records user activity at `now`.
+    ///
+    /// Returns whether the screen was on, otherwise the press only woke the screen
+    /// and shouldn't reach it.
+    pub fn activity(&mut self, now: Instant) -> bool {
+        if self.state == PowerState::Shutdown {
+            return false;
+        }
+        let screen_was_on = self.state.screen_on();
+        self.last_activity = now;
+        self.state = PowerState::Active;
+        screen_was_on
+    }
+
+    /// Moves to the state matching the idle time at `now`.
+    pub fn update(&mut self, now: Instant) -> PowerState {
+        if self.state == PowerState::Shutdown {
+            return self.state;
+        }
+        let idle = now.saturating_duration_since(self.last_activity);
+        let config = &self.config;
+        self.state = if idle >= config.sleep_after {
+            PowerState::Sleep
+        } else if idle >= config.off_after {
+            PowerState::ScreenOff
+        } else if idle >= config.dim_after {
+            PowerState::Dimmed
+        } else {
+            PowerState::Active
+        };
+        self.state
+    }
+
+    /// Requests a safe shutdown, which no activity can undo.
+    pub fn shut_down(&mut self) {
+        self.state = PowerState::Shutdown;
+    }
+
+    /// Called after waking from light sleep. The screen stays off until the
+    /// next press and the sleep countdown starts over.
+    pub fn woke_up(&mut self, now: Instant) {
+        let off = self.config.off_after.min(self.config.sleep_after);
+        self.last_activity = now.checked_sub(off).unwrap_or(now);
+        self.state = PowerState::ScreenOff;
+    }
+
+    /// When the state changes next without any activity.
+    pub fn time_until_change(&self, now: Instant) -> Option<Duration> {
+        let config = &self.config;
+        let next = match self.state {
+            PowerState::Active => config.dim_after,
+            PowerState::Dimmed => config.off_after,
+            PowerState::ScreenOff => config.sleep_after,
+            PowerState::Sleep | PowerState::Shutdown => return None,
+        };
+        Some((self.last_activity + next).saturating_duration_since(now))
+    }
+
+    /// Backlight level in percent for the current state.
+    pub fn backlight(&self) -> u8 {
+        match self.state {
+            PowerState::Active => self.config.brightness,
+            PowerState::Dimmed => self.config.dimmed_brightness.min(self.config.brightness),
+            PowerState::ScreenOff | PowerState::Sleep | PowerState::Shutdown => 0,
+        }
+    }
+}
+
+impl Default for IdlePolicy {
+    fn default() -> Self {
+        Self::new(PowerConfig::default(), Instant::now())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secs(seconds: u64) -> Duration {
+        Duration::from_secs(seconds)
+    }
+
+    #[test]
+    fn dims_turns_off_and_sleeps_when_idle() {
+        let start = Instant::now();
+        let mut policy = IdlePolicy::new(PowerConfig::default(), start);
+        assert_eq!(policy.update(start + secs(19)), PowerState::Active);
+        assert_eq!(policy.backlight(), 100);
+        assert_eq!(policy.update(start + secs(20)), PowerState::Dimmed);
+        assert_eq!(policy.backlight(), 20);
+        assert_eq!(policy.update(start + secs(60)), PowerState::ScreenOff);
+        assert_eq!(policy.backlight(), 0);
+        assert_eq!(policy.update(start + secs(180)), PowerState::Sleep);
+        assert_eq!(policy.time_until_change(start + secs(180)), None);
+    }
+
+    #[test]
+    fn activity_restarts_countdown() {
+        let start = Instant::now();
+        let mut policy = IdlePolicy::new(PowerConfig::default(), start);
+        policy.update(start + secs(30));
+        assert!(
+            policy.activity(start + secs(30)),
+            "dimmed screen is still visible"
+        );
+        assert_eq!(policy.update(start + secs(49)), PowerState::Active);
+        assert_eq!(policy.time_until_change(start + secs(49)), Some(secs(1)));
+    }
+
+    #[test]
+    fn press_on_dark_screen_only_wakes() {
+        let start = Instant::now();
+        let mut policy = IdlePolicy::new(PowerConfig::default(), start);
+        policy.update(start + secs(90));
+        assert!(!policy.activity(start + secs(90)));
+        assert_eq!(policy.state(), PowerState::Active);
+        assert_eq!(policy.backlight(), 100);
+    }
+
+    #[test]
+    fn woken_board_waits_for_a_press() {
+        let start = Instant::now();
+        let mut policy = IdlePolicy::new(PowerConfig::default(), start);
+        policy.update(start + secs(200));
+        let wake = start + secs(600);
+        policy.woke_up(wake);
+        assert_eq!(policy.update(wake), PowerState::ScreenOff);
+        assert_eq!(policy.time_until_change(wake), Some(secs(120)));
+        assert_eq!(policy.update(wake + secs(120)), PowerState::Sleep);
+    }
+
+    #[test]
+    fn shutdown_is_final() {
+        let start = Instant::now();
+        let mut policy = IdlePolicy::new(PowerConfig::default(), start);
+        policy.shut_down();
+        assert!(!policy.activity(start));
+        assert_eq!(policy.update(start), PowerState::Shutdown);
+        assert_eq!(policy.backlight(), 0);
+    }
+
+    #[test]
+    fn dimmed_level_never_exceeds_brightness() {
+        let start = Instant::now();
+        let config = PowerConfig {
+            brightness: 10,
+            ..PowerConfig::default()
+        };
+        let mut policy = IdlePolicy::new(config, start);
+        policy.update(start + secs(30));
+        assert_eq!(policy.backlight(), 10);
+    }
+}
@@ -0,0 +1,133 @@
+use std::time::{Duration, Instant};
+
+/// Upper bound on ticks run in a single frame, so a long stall
+/// (e.g. a slow SPI transfer) doesn't turn into a burst of catch-up ticks.
+const MAX_TICKS_PER_FRAME: u32 = 5;
+
+/// Paces drawing at a target frame rate and the simulation at a fixed tick rate.
+///
+/// Drawing and ticking are decoupled, so animations advance at the same speed
+/// no matter how fast the display can be redrawn.
+#[derive(Debug, Clone)]
+pub struct Scheduler {
+    frame_interval: Duration,
+    tick_interval: Duration,
+    next_frame: Instant,
+    next_tick: Instant,
+}
+
+impl Scheduler {
+    pub fn new(frames_per_second: u32, ticks_per_second: u32) -> Self {
+        assert!(
+            frames_per_second > 0 && ticks_per_second > 0,
+            "Scheduler needs at least one frame and one tick per second"
+        );
+        let now = Instant::now();
+        let tick_interval = Duration::from_secs(1) / ticks_per_second;
+        Self {
+            frame_interval: Duration::from_secs(1) / frames_per_second,
+            tick_interval,
+            next_frame: now,
+            next_tick: now + tick_interval,
+        }
+    }
+
+    /// Fixed simulation step passed to every tick.
+    pub fn tick_interval(&self) -> Duration {
+        self.tick_interval
+    }
+
+    /// How long the caller may block on input before the next frame is due.
+    pub fn time_until_frame(&self, now: Instant) -> Duration {
+        self.next_frame.saturating_duration_since(now)
+    }
+
+    /// Number of fixed ticks that elapsed since the previous call.
+    pub fn take_ticks(&mut self, now: Instant) -> u32 {
+        if now < self.next_tick {
+            return 0;
+        }
+        let behind = (now - self.next_tick).as_nanos() / self.tick_interval.as_nanos();
+        if behind >= u128::from(MAX_TICKS_PER_FRAME) {
+            self.next_tick = now + self.tick_interval;
+            return MAX_TICKS_PER_FRAME;
+        }
+        let ticks = behind as u32 + 1;
+        self.next_tick += self.tick_interval * ticks;
+        ticks
+    }
+
+    /// Schedules the next frame one interval after `now`.
+    pub fn frame_drawn(&mut self, now: Instant) {
+        self.next_frame = now + self.frame_interval;
+    }
+
+    /// Starts counting frames and ticks from `now`, dropping anything pending.
+    pub fn reset(&mut self, now: Instant) {
+        self.next_frame = now;
+        self.next_tick = now + self.tick_interval;
+    }
+}
+
+impl Default for Scheduler {
+    fn default() -> Self {
+        Self::new(30, 20)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MS: Duration = Duration::from_millis(1);
+
+    fn scheduler(start: Instant) -> Scheduler {
+        let mut scheduler = Scheduler::new(25, 10);
+        scheduler.reset(start);
+        scheduler
+    }
+
+    #[test]
+    fn counts_elapsed_ticks() {
+        let start = Instant::now();
+        let mut scheduler = scheduler(start);
+        assert_eq!(scheduler.tick_interval(), 100 * MS);
+        assert_eq!(scheduler.take_ticks(start + 99 * MS), 0);
+        assert_eq!(scheduler.take_ticks(start + 100 * MS), 1);
+        assert_eq!(scheduler.take_ticks(start + 150 * MS), 0);
+        assert_eq!(scheduler.take_ticks(start + 420 * MS), 3);
+        assert_eq!(scheduler.take_ticks(start + 500 * MS), 1);
+    }
+
+    #[test]
+    fn skips_ticks_after_a_long_stall() {
+        let start = Instant::now();
+        let mut scheduler = scheduler(start);
+        let woke_up = start + Duration::from_secs(3600);
+        assert_eq!(scheduler.take_ticks(woke_up), MAX_TICKS_PER_FRAME);
+        assert_eq!(scheduler.take_ticks(woke_up + 99 * MS), 0);
+        assert_eq!(scheduler.take_ticks(woke_up + 100 * MS), 1);
+    }
+
+    #[test]
+    fn paces_frames() {
+        let start = Instant::now();
+        let mut scheduler = scheduler(start);
+        assert_eq!(scheduler.time_until_frame(start), Duration::ZERO);
+        scheduler.frame_drawn(start + 10 * MS);
+        assert_eq!(scheduler.time_until_frame(start + 20 * MS), 30 * MS);
+        assert_eq!(scheduler.time_until_frame(start + 60 * MS), Duration::ZERO);
+
+        let later = start + Duration::from_secs(10);
+        scheduler.reset(later);
+        assert_eq!(scheduler.time_until_frame(later), Duration::ZERO);
+        assert_eq!(scheduler.take_ticks(later + 99 * MS), 0);
+        assert_eq!(scheduler.take_ticks(later + 100 * MS), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one frame")]
+    fn rejects_zero_rates() {
+        Scheduler::new(0, 20);
+    }
+}
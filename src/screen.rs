@@ -0,0 +1,80 @@
+use crate::battery::{BatteryEstimator, BatteryHistory};
+use crate::calibration::Calibration;
+use crate::carousel::Carousel;
+use crate::clock::Clock;
+use crate::input::InputEvent;
+use crate::power::IdlePolicy;
+use crate::theme::Theme;
+use crate::transition::TransitionConfig;
+use mousefood::prelude::*;
+use std::time::{Duration, Instant};
+
+/// What the run loop should do after a screen handled an event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Stay,
+    NextScreen,
+    PreviousScreen,
+}
+
+/// State shared by the [`App`](crate::app::App) with every screen.
+#[derive(Debug, Default)]
+pub struct Context {
+    /// Filtered battery readings, fed with raw samples by the run loop.
+    pub battery: BatteryEstimator,
+    /// Applied to every raw battery reading before it reaches [`Context::battery`].
+    pub calibration: Calibration,
+    /// Filtered battery voltage sampled at a fixed interval.
+    pub battery_history: BatteryHistory,
+    /// Backlight and sleep state, driven by button activity.
+    pub power: IdlePolicy,
+    /// Wall-clock time, set by the run loop from the best source available.
+    pub clock: Clock,
+    /// Switches screens on its own, paused by button activity.
+    pub carousel: Carousel,
+    /// How the app animates switching from one screen to another.
+    pub transitions: TransitionConfig,
+    /// Colours every screen draws with.
+    pub theme: Theme,
+}
+
+impl Context {
+    /// Calibrates a raw reading of the voltage at the battery ADC pin and records it.
+    pub fn push_battery_sample(&mut self, adc_millivolts: u16, now: Instant) {
+        let millivolts = self.calibration.apply(adc_millivolts);
+        self.battery.push(millivolts, now);
+        if let Some(filtered) = self.battery.millivolts() {
+            self.battery_history.record(filtered, now);
+        }
+    }
+}
+
+/// A single demo screen driven by [`App`](crate::app::App).
+pub trait Screen {
+    /// Short lowercase name, used to pick the screen from the console.
+    fn name(&self) -> &'static str;
+
+    /// Called every time the screen becomes active.
+    fn init(&mut self, _ctx: &mut Context) {}
+
+    /// Handles a single input event. By default a short or long press moves on
+    /// to the next screen and a double press goes back.
+    fn handle_event(&mut self, event: InputEvent, _ctx: &mut Context) -> Action {
+        match event {
+            InputEvent::ShortPress | InputEvent::LongPress => Action::NextScreen,
+            InputEvent::DoublePress => Action::PreviousScreen,
+            InputEvent::Repeat => Action::Stay,
+        }
+    }
+
+    /// Advances the screen state by one fixed simulation step of `dt`.
+    fn tick(&mut self, _dt: Duration, _ctx: &mut Context) {}
+
+    /// What the button does right now, shown in the status bar.
+    fn hints(&self) -> &'static str {
+        "[S1] next  [2x] back"
+    }
+
+    /// Draws the screen into `area`, which leaves room for the status bar.
+    fn draw(&self, frame: &mut Frame, area: Rect, ctx: &Context);
+}
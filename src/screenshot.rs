@@ -0,0 +1,319 @@
+mod base64;
+mod bmp;
+
+pub use base64::Base64Writer;
+pub use bmp::write_bmp;
+
+use embedded_graphics::prelude::Size;
+use mousefood::prelude::*;
+use mousefood::ratatui::buffer::Buffer;
+use std::fmt;
+use std::io::{self, Write};
+
+/// First line of an exported screenshot, followed by the format and size.
+const BEGIN_MARKER: &str = "-----BEGIN SCREENSHOT ";
+const END_MARKER: &str = "-----END SCREENSHOT-----";
+
+/// How a frame is written to the serial console.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ScreenshotFormat {
+    /// The symbols of every cell, without colours.
+    #[default]
+    Text,
+    /// Text with ANSI escape codes for colours and modifiers, for `cat` in a terminal.
+    Ansi,
+    /// The pixels as drawn on the panel, as a base64 encoded BMP.
+    Bmp,
+}
+
+impl ScreenshotFormat {
+    pub const ALL: [ScreenshotFormat; 3] = [Self::Text, Self::Ansi, Self::Bmp];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ScreenshotFormat::Text => "text",
+            ScreenshotFormat::Ansi => "ansi",
+            ScreenshotFormat::Bmp => "bmp",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|format| format.name() == name)
+    }
+
+    /// Extension of the file a decoded screenshot is saved as.
+    pub fn extension(self) -> &'static str {
+        match self {
+            ScreenshotFormat::Text => "txt",
+            ScreenshotFormat::Ansi => "ans",
+            ScreenshotFormat::Bmp => "bmp",
+        }
+    }
+}
+
+/// Writes `buffer` between marker lines that [`decode`] finds in a serial log.
+///
+/// `size` is the display resolution, only used to draw the pixels of a BMP.
+/// At 115200 baud a BMP of the T-Display takes about eight seconds.
+pub fn export(
+    format: ScreenshotFormat,
+    buffer: &Buffer,
+    size: Size,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let (width, height) = match format {
+        ScreenshotFormat::Text | ScreenshotFormat::Ansi => {
+            (u32::from(buffer.area.width), u32::from(buffer.area.height))
+        }
+        ScreenshotFormat::Bmp => (size.width, size.height),
+    };
+    writeln!(out, "{BEGIN_MARKER}{} {width}x{height}-----", format.name())?;
+    match format {
+        ScreenshotFormat::Text => out.write_all(to_text(buffer).as_bytes())?,
+        ScreenshotFormat::Ansi => out.write_all(to_ansi(buffer).as_bytes())?,
+        ScreenshotFormat::Bmp => {
+            let mut writer = Base64Writer::new(&mut *out);
+            write_bmp(buffer, size, &mut writer)?;
+            writer.finish()?;
+        }
+    }
+    writeln!(out, "{END_MARKER}")?;
+    out.flush()
+}
+
+/// The symbols of `buffer`, one line per row without trailing spaces.
+pub fn to_text(buffer: &Buffer) -> String {
+    let mut text = String::new();
+    for y in buffer.area.top()..buffer.area.bottom() {
+        let start = text.len();
+        for x in buffer.area.left()..buffer.area.right() {
+            text.push_str(buffer[(x, y)].symbol());
+        }
+        text.truncate(start + text[start..].trim_end().len());
+        text.push('\n');
+    }
+    text
+}
+
+/// The symbols of `buffer` with SGR escape codes wherever the style changes.
+/// Every line ends with a reset, so lines can be shown on their own.
+pub fn to_ansi(buffer: &Buffer) -> String {
+    let mut text = String::new();
+    for y in buffer.area.top()..buffer.area.bottom() {
+        let mut style = None;
+        for x in buffer.area.left()..buffer.area.right() {
+            let cell = &buffer[(x, y)];
+            let cell_style = (cell.fg, cell.bg, cell.modifier);
+            if style != Some(cell_style) {
+                text.push_str(&sgr(cell.fg, cell.bg, cell.modifier));
+                style = Some(cell_style);
+            }
+            text.push_str(cell.symbol());
+        }
+        text.push_str("\x1b[0m\n");
+    }
+    text
+}
+
+fn sgr(fg: Color, bg: Color, modifier: Modifier) -> String {
+    const MODIFIERS: [(Modifier, u8); 9] = [
+        (Modifier::BOLD, 1),
+        (Modifier::DIM, 2),
+        (Modifier::ITALIC, 3),
+        (Modifier::UNDERLINED, 4),
+        (Modifier::SLOW_BLINK, 5),
+        (Modifier::RAPID_BLINK, 6),
+        (Modifier::REVERSED, 7),
+        (Modifier::HIDDEN, 8),
+        (Modifier::CROSSED_OUT, 9),
+    ];
+    let mut codes = vec!["0".to_string()];
+    codes.extend(
+        MODIFIERS
+            .iter()
+            .filter(|(flag, _)| modifier.contains(*flag))
+            .map(|(_, code)| code.to_string()),
+    );
+    codes.push(color_code(fg, false));
+    codes.push(color_code(bg, true));
+    format!("\x1b[{}m", codes.join(";"))
+}
+
+fn color_code(color: Color, background: bool) -> String {
+    let offset = if background { 10 } else { 0 };
+    let code = match color {
+        Color::Reset => 39,
+        Color::Black => 30,
+        Color::Red => 31,
+        Color::Green => 32,
+        Color::Yellow => 33,
+        Color::Blue => 34,
+        Color::Magenta => 35,
+        Color::Cyan => 36,
+        Color::Gray => 37,
+        Color::DarkGray => 90,
+        Color::LightRed => 91,
+        Color::LightGreen => 92,
+        Color::LightYellow => 93,
+        Color::LightBlue => 94,
+        Color::LightMagenta => 95,
+        Color::LightCyan => 96,
+        Color::White => 97,
+        Color::Rgb(r, g, b) => return format!("{};2;{r};{g};{b}", 38 + offset),
+        Color::Indexed(index) => return format!("{};5;{index}", 38 + offset),
+    };
+    (code + offset).to_string()
+}
+
+/// A screenshot found in a serial log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Screenshot {
+    pub format: ScreenshotFormat,
+    /// Width in cells for text, in pixels for a BMP.
+    pub width: u32,
+    pub height: u32,
+    /// The text, or the BMP file.
+    pub data: Vec<u8>,
+}
+
+/// Why a screenshot in a serial log can't be decoded, with the line it starts at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeError {
+    InvalidHeader(usize),
+    /// Usually a log message printed in the middle of the image.
+    InvalidBase64(usize),
+    /// The log ends before the end marker.
+    Unterminated(usize),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DecodeError::InvalidHeader(line) => {
+                write!(f, "line {line}: invalid screenshot header")
+            }
+            DecodeError::InvalidBase64(line) => {
+                write!(f, "screenshot at line {line}: invalid base64 data")
+            }
+            DecodeError::Unterminated(line) => {
+                write!(f, "screenshot at line {line}: missing end marker")
+            }
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// Finds every exported screenshot in `log`, skipping anything printed around them.
+pub fn decode(log: &str) -> Result<Vec<Screenshot>, DecodeError> {
+    let mut screenshots = Vec::new();
+    let mut lines = log
+        .lines()
+        .map(|line| line.trim_end_matches('\r'))
+        .enumerate();
+    while let Some((index, line)) = lines.next() {
+        let Some(header) = line.strip_prefix(BEGIN_MARKER) else {
+            continue;
+        };
+        let line_number = index + 1;
+        let (format, width, height) =
+            parse_header(header).ok_or(DecodeError::InvalidHeader(line_number))?;
+
+        let mut body = String::new();
+        let mut terminated = false;
+        for (_, line) in lines.by_ref() {
+            if line == END_MARKER {
+                terminated = true;
+                break;
+            }
+            body.push_str(line);
+            body.push('\n');
+        }
+        if !terminated {
+            return Err(DecodeError::Unterminated(line_number));
+        }
+        let data = match format {
+            ScreenshotFormat::Text | ScreenshotFormat::Ansi => body.into_bytes(),
+            ScreenshotFormat::Bmp => {
+                base64::decode(&body).ok_or(DecodeError::InvalidBase64(line_number))?
+            }
+        };
+        screenshots.push(Screenshot {
+            format,
+            width,
+            height,
+            data,
+        });
+    }
+    Ok(screenshots)
+}
+
+/// Parses `<format> <width>x<height>-----`.
+fn parse_header(header: &str) -> Option<(ScreenshotFormat, u32, u32)> {
+    let (format, size) = header.strip_suffix("-----")?.split_once(' ')?;
+    let (width, height) = size.split_once('x')?;
+    Some((
+        ScreenshotFormat::from_name(format)?,
+        width.parse().ok()?,
+        height.parse().ok()?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn buffer() -> Buffer {
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 4, 2));
+        buffer.set_string(0, 0, "ok", Style::new().green().bold());
+        buffer.set_string(0, 1, "!", Style::new().on_red());
+        buffer
+    }
+
+    #[test]
+    fn formats_text_and_ansi() {
+        assert_eq!(to_text(&buffer()), "ok\n!\n");
+        assert_eq!(
+            to_ansi(&buffer()),
+            "\x1b[0;1;32;49mok\x1b[0;39;49m  \x1b[0m\n\x1b[0;39;41m!\x1b[0;39;49m   \x1b[0m\n"
+        );
+    }
+
+    #[test]
+    fn decodes_exports_from_a_log() {
+        let size = Size::new(24, 20);
+        let mut log = b"boot\r\n".to_vec();
+        export(ScreenshotFormat::Text, &buffer(), size, &mut log).unwrap();
+        log.extend_from_slice(b"I (120) app: running\n");
+        export(ScreenshotFormat::Bmp, &buffer(), size, &mut log).unwrap();
+
+        let screenshots = decode(&String::from_utf8(log).unwrap()).unwrap();
+        assert_eq!(screenshots.len(), 2);
+        assert_eq!(screenshots[0].format, ScreenshotFormat::Text);
+        assert_eq!((screenshots[0].width, screenshots[0].height), (4, 2));
+        assert_eq!(screenshots[0].data, b"ok\n!\n");
+
+        let mut bmp = Vec::new();
+        write_bmp(&buffer(), size, &mut bmp).unwrap();
+        assert_eq!(screenshots[1].format, ScreenshotFormat::Bmp);
+        assert_eq!((screenshots[1].width, screenshots[1].height), (24, 20));
+        assert_eq!(screenshots[1].data, bmp);
+    }
+
+    #[test]
+    fn reports_broken_screenshots() {
+        let header = "-----BEGIN SCREENSHOT bmp 24x20-----";
+        assert_eq!(
+            decode(&format!("boot\n{header}\nQk0=\n")),
+            Err(DecodeError::Unterminated(2))
+        );
+        assert_eq!(
+            decode(&format!("{header}\nQk0=\nI (120) app: log\n{END_MARKER}\n")),
+            Err(DecodeError::InvalidBase64(1))
+        );
+        assert_eq!(
+            decode("-----BEGIN SCREENSHOT png 24x20-----\n"),
+            Err(DecodeError::InvalidHeader(1))
+        );
+    }
+}
@@ -0,0 +1,145 @@
+use embedded_graphics::pixelcolor::{IntoStorage, Rgb565};
+use embedded_graphics::prelude::{DrawTarget, OriginDimensions, Pixel, RgbColor, Size};
+use mousefood::prelude::*;
+use mousefood::ratatui::buffer::Buffer;
+use std::convert::Infallible;
+use std::io::{self, Write};
+
+/// Size of the file and info headers plus the three RGB565 bit masks.
+const HEADER_BYTES: u32 = 14 + 40 + 12;
+
+/// Renders `buffer` the way mousefood draws it on a `size` display and writes it
+/// as a 16-bit RGB565 bitmap.
+///
+/// Only one line of cells is rendered at a time, so even the larger panels fit
+/// in the heap of a board without PSRAM.
+pub fn write_bmp(buffer: &Buffer, size: Size, out: &mut impl Write) -> io::Result<()> {
+    let row_bytes = (size.width * 2).next_multiple_of(4);
+    let image_bytes = row_bytes * size.height;
+    let lines = u32::from(buffer.area.height).max(1);
+    let cell_height = (size.height / lines).max(1);
+
+    // BITMAPFILEHEADER
+    out.write_all(b"BM")?;
+    out.write_all(&(HEADER_BYTES + image_bytes).to_le_bytes())?;
+    out.write_all(&0u32.to_le_bytes())?;
+    out.write_all(&HEADER_BYTES.to_le_bytes())?;
+    // BITMAPINFOHEADER with BI_BITFIELDS, rows stored bottom-up
+    out.write_all(&40u32.to_le_bytes())?;
+    out.write_all(&size.width.to_le_bytes())?;
+    out.write_all(&size.height.to_le_bytes())?;
+    out.write_all(&1u16.to_le_bytes())?;
+    out.write_all(&16u16.to_le_bytes())?;
+    out.write_all(&3u32.to_le_bytes())?;
+    out.write_all(&image_bytes.to_le_bytes())?;
+    // 72 DPI, no palette
+    for value in [2835u32, 2835, 0, 0] {
+        out.write_all(&value.to_le_bytes())?;
+    }
+    for mask in [0xf800u32, 0x07e0, 0x001f] {
+        out.write_all(&mask.to_le_bytes())?;
+    }
+
+    let mut row = vec![0; row_bytes as usize];
+    let mut band: Option<Band> = None;
+    for y in (0..size.height).rev() {
+        let line = y / cell_height;
+        if band.as_ref().is_none_or(|band| band.line != line) {
+            band = Some(Band::render(buffer, line, cell_height, size)?);
+        }
+        let band = band.as_ref().expect("band was just rendered");
+        for (x, pixel) in band.row(y).iter().enumerate() {
+            row[x * 2..x * 2 + 2].copy_from_slice(&pixel.into_storage().to_le_bytes());
+        }
+        out.write_all(&row)?;
+    }
+    Ok(())
+}
+
+/// The pixel rows of a single line of cells, drawn by mousefood.
+struct Band {
+    size: Size,
+    line: u32,
+    top: u32,
+    height: u32,
+    pixels: Vec<Rgb565>,
+}
+
+impl Band {
+    fn render(buffer: &Buffer, line: u32, height: u32, size: Size) -> io::Result<Self> {
+        let mut band = Self {
+            size,
+            line,
+            top: line * height,
+            height,
+            pixels: vec![Rgb565::BLACK; (size.width * height) as usize],
+        };
+        // Pixels below the last line of cells stay black, as on the panel.
+        let line = u16::try_from(line).ok();
+        if let Some(line) = line.filter(|&line| line < buffer.area.height) {
+            let y = buffer.area.y + line;
+            let cells = (buffer.area.left()..buffer.area.right()).map(|x| (x, y, &buffer[(x, y)]));
+            let mut backend = EmbeddedBackend::new(&mut band, Default::default());
+            backend.draw(cells)?;
+            backend.flush()?;
+        }
+        Ok(band)
+    }
+
+    fn row(&self, y: u32) -> &[Rgb565] {
+        let start = ((y - self.top) * self.size.width) as usize;
+        &self.pixels[start..start + self.size.width as usize]
+    }
+}
+
+impl OriginDimensions for Band {
+    fn size(&self) -> Size {
+        self.size
+    }
+}
+
+impl DrawTarget for Band {
+    type Color = Rgb565;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            let Ok((x, y)) = <(u32, u32)>::try_from(point) else {
+                continue;
+            };
+            if x < self.size.width && (self.top..self.top + self.height).contains(&y) {
+                self.pixels[((y - self.top) * self.size.width + x) as usize] = color;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_u32(bytes: &[u8], at: usize) -> u32 {
+        u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn writes_padded_bitfield_header() {
+        let buffer = Buffer::empty(Rect::new(0, 0, 1, 2));
+        let mut bytes = Vec::new();
+        write_bmp(&buffer, Size::new(7, 20), &mut bytes).unwrap();
+
+        // 7 pixels of 2 bytes are padded to 16 bytes per row.
+        assert_eq!(bytes.len(), 66 + 16 * 20);
+        assert_eq!(&bytes[..2], b"BM");
+        assert_eq!(read_u32(&bytes, 2), bytes.len() as u32);
+        assert_eq!(read_u32(&bytes, 10), 66);
+        assert_eq!(read_u32(&bytes, 18), 7);
+        assert_eq!(read_u32(&bytes, 22), 20);
+        assert_eq!(read_u32(&bytes, 30), 3);
+        assert_eq!(read_u32(&bytes, 54), 0xf800);
+    }
+}
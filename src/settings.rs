@@ -0,0 +1,439 @@
+mod file;
+
+pub use file::FileBackend;
+
+use crate::app::App;
+use crate::calibration::Calibration;
+use crate::carousel::{CarouselConfig, format_playlist, parse_playlist};
+use crate::power::PowerConfig;
+use crate::theme::Theme;
+use std::fmt::{self, Write};
+use std::io;
+use std::time::{Duration, Instant};
+
+/// Layout of the stored record, written as its first line.
+///
+/// Fields missing from a record keep their defaults, so adding a field needs no new
+/// version. Bump it when a field changes meaning and convert older records in
+/// [`Settings::from_bytes`].
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// How long settings have to stay the same before they are written, so paging
+/// through screens doesn't write the flash on every press.
+const SAVE_DELAY: Duration = Duration::from_secs(5);
+
+/// Everything the demo remembers across a reboot.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+    /// Screen shown at startup, the first one if `None`. Not kept while the
+    /// carousel is on, since it picks the screen anyway.
+    pub last_screen: Option<String>,
+    /// Backlight level in percent while in use.
+    pub brightness: u8,
+    pub theme: Theme,
+    pub carousel: CarouselConfig,
+    /// Per-device correction of the battery reading, see [`Calibration`]. The
+    /// divider is the board profile's until one is stored.
+    pub battery_divider: Option<f32>,
+    pub battery_gain: f32,
+    pub battery_offset_millivolts: i16,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        let calibration = Calibration::default();
+        Self {
+            last_screen: None,
+            brightness: PowerConfig::default().brightness,
+            theme: Theme::default(),
+            carousel: CarouselConfig::selected(),
+            battery_divider: None,
+            battery_gain: calibration.gain,
+            battery_offset_millivolts: calibration.offset_millivolts,
+        }
+    }
+}
+
+impl Settings {
+    /// The settings `app` currently runs with.
+    pub fn from_app(app: &App) -> Self {
+        let ctx = app.context();
+        let last_screen =
+            (!ctx.carousel.config.enabled).then(|| app.screen_names()[app.current()].to_string());
+        Self {
+            last_screen,
+            brightness: ctx.power.config.brightness,
+            theme: ctx.theme,
+            carousel: ctx.carousel.config.clone(),
+            battery_divider: Some(ctx.calibration.divider),
+            battery_gain: ctx.calibration.gain,
+            battery_offset_millivolts: ctx.calibration.offset_millivolts,
+        }
+    }
+
+    /// Makes `app` run with these settings.
+    ///
+    /// A last screen or playlist entry naming a screen that no longer exists is skipped.
+    pub fn apply(&self, app: &mut App) {
+        let names = app.screen_names();
+        let last_screen = self.last_screen.as_deref();
+        if let Some(index) = last_screen.and_then(|name| names.iter().position(|&n| n == name)) {
+            app.show(index);
+        }
+        let mut carousel = self.carousel.clone();
+        carousel
+            .playlist
+            .retain(|slide| names.contains(&slide.screen.as_str()));
+        app.set_carousel(carousel)
+            .expect("the playlist only names existing screens");
+        let ctx = app.context_mut();
+        ctx.power.config.brightness = self.brightness;
+        ctx.theme = self.theme;
+        if let Some(divider) = self.battery_divider {
+            ctx.calibration.divider = divider;
+        }
+        ctx.calibration.gain = self.battery_gain;
+        ctx.calibration.offset_millivolts = self.battery_offset_millivolts;
+    }
+
+    /// A `key=value` line per field after a `version=` line.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let carousel = &self.carousel;
+        let fields = [
+            ("last_screen", self.last_screen.clone().unwrap_or_default()),
+            ("brightness", self.brightness.to_string()),
+            ("theme", self.theme.name.to_string()),
+            // After the theme, which resets it.
+            ("accent", self.theme.accent.to_string()),
+            ("carousel", carousel.enabled.to_string()),
+            ("playlist", format_playlist(&carousel.playlist)),
+            ("shuffle", carousel.shuffle.to_string()),
+            ("dwell", carousel.dwell.as_secs().to_string()),
+            ("resume_after", carousel.resume_after.as_secs().to_string()),
+            (
+                "battery_divider",
+                self.battery_divider
+                    .map(|divider| divider.to_string())
+                    .unwrap_or_default(),
+            ),
+            ("battery_gain", self.battery_gain.to_string()),
+            ("battery_offset", self.battery_offset_millivolts.to_string()),
+        ];
+        let mut record = format!("version={SCHEMA_VERSION}\n");
+        for (key, value) in fields {
+            writeln!(record, "{key}={value}").unwrap();
+        }
+        record.into_bytes()
+    }
+
+    /// Reads a record written by [`Settings::to_bytes`] with any schema up to
+    /// [`SCHEMA_VERSION`].
+    ///
+    /// Unknown keys and invalid values are skipped, leaving the default for the field.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SettingsError> {
+        let record = std::str::from_utf8(bytes).ok();
+        let Some(record) = record.and_then(|record| record.strip_prefix("version=")) else {
+            // Version 0, the calibration blob firmware kept before there were settings.
+            let calibration = Calibration::from_bytes(bytes).ok_or(SettingsError::Invalid)?;
+            return Ok(Self {
+                battery_divider: Some(calibration.divider),
+                battery_gain: calibration.gain,
+                battery_offset_millivolts: calibration.offset_millivolts,
+                ..Self::default()
+            });
+        };
+        let (version, fields) = record.split_once('\n').unwrap_or((record, ""));
+        let version: u32 = version.trim().parse().map_err(|_| SettingsError::Invalid)?;
+        if version > SCHEMA_VERSION {
+            return Err(SettingsError::NewerVersion(version));
+        }
+        let mut settings = Self::default();
+        for (key, value) in fields.lines().filter_map(|line| line.split_once('=')) {
+            settings.set_field(key, value.trim());
+        }
+        Ok(settings)
+    }
+
+    /// Sets the field stored under `key`, `None` if the key is unknown or the value invalid.
+    fn set_field(&mut self, key: &str, value: &str) -> Option<()> {
+        let carousel = &mut self.carousel;
+        match key {
+            "last_screen" => self.last_screen = (!value.is_empty()).then(|| value.to_string()),
+            "brightness" => {
+                self.brightness = value.parse().ok().filter(|&percent| percent <= 100)?
+            }
+            "theme" => self.theme = *Theme::by_name(value)?,
+            "accent" => self.theme.accent = value.parse().ok()?,
+            "carousel" => carousel.enabled = value.parse().ok()?,
+            "playlist" => carousel.playlist = parse_playlist(value).ok()?,
+            "shuffle" => carousel.shuffle = value.parse().ok()?,
+            "dwell" => carousel.dwell = Duration::from_secs(value.parse().ok()?),
+            "resume_after" => carousel.resume_after = Duration::from_secs(value.parse().ok()?),
+            "battery_divider" => {
+                self.battery_divider = match value {
+                    "" => None,
+                    _ => Some(value.parse().ok().filter(|&divider| divider >= 1.0)?),
+                }
+            }
+            "battery_gain" => self.battery_gain = value.parse().ok().filter(|&gain| gain > 0.0)?,
+            "battery_offset" => self.battery_offset_millivolts = value.parse().ok()?,
+            // Written by newer firmware with the same schema.
+            _ => return None,
+        }
+        Some(())
+    }
+}
+
+#[derive(Debug)]
+pub enum SettingsError {
+    Io(io::Error),
+    /// The stored record is neither settings nor an old calibration.
+    Invalid,
+    /// Written by newer firmware with a schema this one doesn't know.
+    NewerVersion(u32),
+}
+
+impl fmt::Display for SettingsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SettingsError::Io(error) => write!(f, "can't read settings: {error}"),
+            SettingsError::Invalid => f.write_str("stored settings are corrupt"),
+            SettingsError::NewerVersion(version) => write!(
+                f,
+                "stored settings have version {version}, newer than {SCHEMA_VERSION}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SettingsError {}
+
+impl From<io::Error> for SettingsError {
+    fn from(error: io::Error) -> Self {
+        SettingsError::Io(error)
+    }
+}
+
+/// Where the settings record is kept.
+pub trait SettingsBackend {
+    /// The stored record, `None` if nothing was saved yet.
+    fn read(&mut self) -> io::Result<Option<Vec<u8>>>;
+
+    /// Replaces the stored record.
+    fn write(&mut self, record: &[u8]) -> io::Result<()>;
+}
+
+/// Loads settings at startup and saves them once they stop changing.
+pub struct SettingsStore<B> {
+    backend: B,
+    /// What the backend holds, so unchanged settings aren't written again.
+    saved: Option<Settings>,
+    /// Changed settings and when they were last seen changing.
+    pending: Option<(Settings, Instant)>,
+}
+
+impl<B: SettingsBackend> SettingsStore<B> {
+    pub fn new(backend: B) -> Self {
+        Self {
+            backend,
+            saved: None,
+            pending: None,
+        }
+    }
+
+    /// The stored settings, the defaults if nothing was saved yet.
+    pub fn load(&mut self) -> Result<Settings, SettingsError> {
+        let settings = match self.backend.read()? {
+            Some(record) => Settings::from_bytes(&record)?,
+            None => Settings::default(),
+        };
+        self.saved = Some(settings.clone());
+        Ok(settings)
+    }
+
+    /// Records the settings in use at `now`, writing them once they have stayed
+    /// the same for a few seconds.
+    pub fn update(&mut self, settings: Settings, now: Instant) -> io::Result<()> {
+        if self.saved.as_ref() == Some(&settings) {
+            self.pending = None;
+            return Ok(());
+        }
+        match &self.pending {
+            Some((pending, since)) if *pending == settings => {
+                if now.saturating_duration_since(*since) >= SAVE_DELAY {
+                    self.flush()?;
+                }
+            }
+            _ => self.pending = Some((settings, now)),
+        }
+        Ok(())
+    }
+
+    /// Writes changed settings right away, e.g. before going to sleep.
+    pub fn flush(&mut self) -> io::Result<()> {
+        if let Some((settings, _)) = self.pending.take() {
+            self.backend.write(&settings.to_bytes())?;
+            self.saved = Some(settings);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::carousel::Slide;
+    use crate::gauge::GaugeApp;
+    use crate::ratatui_logo::RatatuiLogoApp;
+    use crate::theme::HIGH_CONTRAST;
+    use mousefood::prelude::Color;
+
+    /// Keeps the record in memory and counts the writes.
+    #[derive(Default)]
+    struct MemoryBackend {
+        record: Option<Vec<u8>>,
+        writes: usize,
+    }
+
+    impl SettingsBackend for &mut MemoryBackend {
+        fn read(&mut self) -> io::Result<Option<Vec<u8>>> {
+            Ok(self.record.clone())
+        }
+
+        fn write(&mut self, record: &[u8]) -> io::Result<()> {
+            self.record = Some(record.to_vec());
+            self.writes += 1;
+            Ok(())
+        }
+    }
+
+    fn customized() -> Settings {
+        Settings {
+            last_screen: Some("chart".into()),
+            brightness: 40,
+            theme: Theme {
+                accent: Color::Magenta,
+                ..HIGH_CONTRAST
+            },
+            carousel: CarouselConfig {
+                enabled: true,
+                playlist: vec![Slide {
+                    screen: "logo".into(),
+                    dwell: Some(Duration::from_secs(5)),
+                }],
+                shuffle: true,
+                dwell: Duration::from_secs(12),
+                resume_after: Duration::from_secs(45),
+            },
+            battery_divider: Some(2.13),
+            battery_gain: 1.037,
+            battery_offset_millivolts: -25,
+        }
+    }
+
+    #[test]
+    fn skips_screens_that_no_longer_exist() {
+        let mut app = App::new(vec![
+            Box::new(RatatuiLogoApp::new()),
+            Box::new(GaugeApp::new()),
+        ]);
+        let mut settings = customized();
+        settings.carousel.playlist.insert(
+            0,
+            Slide {
+                screen: "removed".into(),
+                dwell: None,
+            },
+        );
+        settings.apply(&mut app);
+        assert_eq!(app.current(), 0);
+        assert_eq!(app.context().carousel.config, customized().carousel);
+        assert_eq!(app.context().power.config.brightness, 40);
+    }
+
+    #[test]
+    fn round_trips() {
+        let settings = customized();
+        assert_eq!(
+            Settings::from_bytes(&settings.to_bytes()).unwrap(),
+            settings
+        );
+        let defaults = Settings::default();
+        assert_eq!(
+            Settings::from_bytes(&defaults.to_bytes()).unwrap(),
+            defaults
+        );
+    }
+
+    #[test]
+    fn migrates_the_calibration_blob() {
+        let calibration = Calibration {
+            divider: 2.0,
+            gain: 0.97,
+            offset_millivolts: -12,
+        };
+        let settings = Settings::from_bytes(&calibration.to_bytes()).unwrap();
+        assert_eq!(settings.battery_divider, Some(2.0));
+        assert_eq!(settings.battery_gain, 0.97);
+        assert_eq!(settings.battery_offset_millivolts, -12);
+        assert_eq!(settings.brightness, Settings::default().brightness);
+        assert!(matches!(
+            Settings::from_bytes(&[0xff; 7]),
+            Err(SettingsError::Invalid)
+        ));
+    }
+
+    #[test]
+    fn keeps_defaults_for_missing_and_invalid_fields() {
+        let record = b"version=1\nbrightness=40\ntheme=neon\ndwell=soon\nfuture=1\n";
+        let settings = Settings::from_bytes(record).unwrap();
+        assert_eq!(
+            settings,
+            Settings {
+                brightness: 40,
+                ..Settings::default()
+            }
+        );
+        assert!(matches!(
+            Settings::from_bytes(b"version=2\nbrightness=40\n"),
+            Err(SettingsError::NewerVersion(2))
+        ));
+        assert!(matches!(
+            Settings::from_bytes(b"version=one\n"),
+            Err(SettingsError::Invalid)
+        ));
+    }
+
+    #[test]
+    fn saves_once_settings_settle() {
+        let mut backend = MemoryBackend::default();
+        let mut store = SettingsStore::new(&mut backend);
+        assert_eq!(store.load().unwrap(), Settings::default());
+
+        let start = Instant::now();
+        store.update(Settings::default(), start).unwrap();
+        store.update(customized(), start).unwrap();
+        store
+            .update(customized(), start + Duration::from_secs(4))
+            .unwrap();
+        store.flush().unwrap();
+        store.update(customized(), start + SAVE_DELAY).unwrap();
+        drop(store);
+        assert_eq!(backend.writes, 1);
+
+        let mut store = SettingsStore::new(&mut backend);
+        assert_eq!(store.load().unwrap(), customized());
+        let changed = Settings {
+            brightness: 70,
+            ..customized()
+        };
+        store.update(changed.clone(), start).unwrap();
+        store.update(changed.clone(), start + SAVE_DELAY).unwrap();
+        drop(store);
+        assert_eq!(backend.writes, 2);
+        assert_eq!(
+            Settings::from_bytes(backend.record.as_ref().unwrap()).unwrap(),
+            changed
+        );
+    }
+}
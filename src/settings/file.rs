@@ -0,0 +1,52 @@
+use crate::settings::SettingsBackend;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+/// Keeps the settings record in a file, for the simulator.
+#[derive(Debug, Clone)]
+pub struct FileBackend {
+    path: PathBuf,
+}
+
+impl FileBackend {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl SettingsBackend for FileBackend {
+    fn read(&mut self) -> io::Result<Option<Vec<u8>>> {
+        match fs::read(&self.path) {
+            Ok(record) => Ok(Some(record)),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+
+    fn write(&mut self, record: &[u8]) -> io::Result<()> {
+        // Renamed into place, so a crash can't leave half a record behind.
+        let partial = self.path.with_extension("partial");
+        fs::write(&partial, record)?;
+        fs::rename(&partial, &self.path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::process;
+
+    #[test]
+    fn reads_what_it_wrote() {
+        let path = env::temp_dir().join(format!("demo-settings-{}.txt", process::id()));
+        let mut backend = FileBackend::new(&path);
+        assert_eq!(backend.read().unwrap(), None);
+        backend.write(b"version=1\n").unwrap();
+        backend.write(b"version=1\nbrightness=40\n").unwrap();
+        let record = backend.read().unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(record.as_deref(), Some(&b"version=1\nbrightness=40\n"[..]));
+    }
+}
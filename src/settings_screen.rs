@@ -0,0 +1,420 @@
+use crate::helpers::center;
+use crate::input::InputEvent;
+use crate::screen::{Action, Context, Screen};
+use crate::theme::THEMES;
+use mousefood::prelude::*;
+use mousefood::ratatui::widgets::{Block, Cell, Clear, Padding, Paragraph, Row, Table, TableState};
+use std::ops::RangeInclusive;
+use std::time::Duration;
+
+/// Accent colours offered by the settings screen.
+const ACCENTS: [(&str, Color); 8] = [
+    ("yellow", Color::Yellow),
+    ("orange", Color::Rgb(230, 159, 0)),
+    ("cyan", Color::Cyan),
+    ("green", Color::Green),
+    ("magenta", Color::Magenta),
+    ("red", Color::Red),
+    ("blue", Color::Rgb(0, 90, 180)),
+    ("white", Color::White),
+];
+const VALUE_WIDTH: u16 = 13;
+
+/// How an entry reads and changes its value in the [`Context`].
+enum EntryKind {
+    Bool {
+        get: fn(&Context) -> bool,
+        set: fn(&mut Context, bool),
+    },
+    /// One of `count` options, shown by `name`.
+    Choice {
+        count: usize,
+        name: fn(usize) -> &'static str,
+        get: fn(&Context) -> usize,
+        set: fn(&mut Context, usize),
+    },
+    Range {
+        range: RangeInclusive<i32>,
+        step: i32,
+        unit: &'static str,
+        get: fn(&Context) -> i32,
+        set: fn(&mut Context, i32),
+    },
+    /// One of a palette of named colours.
+    Color {
+        palette: &'static [(&'static str, Color)],
+        get: fn(&Context) -> Color,
+        set: fn(&mut Context, Color),
+    },
+}
+
+/// One line of the settings screen.
+pub struct Entry {
+    label: &'static str,
+    kind: EntryKind,
+}
+
+impl Entry {
+    pub fn bool(
+        label: &'static str,
+        get: fn(&Context) -> bool,
+        set: fn(&mut Context, bool),
+    ) -> Self {
+        Self {
+            label,
+            kind: EntryKind::Bool { get, set },
+        }
+    }
+
+    pub fn choice(
+        label: &'static str,
+        count: usize,
+        name: fn(usize) -> &'static str,
+        get: fn(&Context) -> usize,
+        set: fn(&mut Context, usize),
+    ) -> Self {
+        Self {
+            label,
+            kind: EntryKind::Choice {
+                count,
+                name,
+                get,
+                set,
+            },
+        }
+    }
+
+    pub fn range(
+        label: &'static str,
+        range: RangeInclusive<i32>,
+        step: i32,
+        unit: &'static str,
+        get: fn(&Context) -> i32,
+        set: fn(&mut Context, i32),
+    ) -> Self {
+        Self {
+            label,
+            kind: EntryKind::Range {
+                range,
+                step,
+                unit,
+                get,
+                set,
+            },
+        }
+    }
+
+    pub fn color(
+        label: &'static str,
+        palette: &'static [(&'static str, Color)],
+        get: fn(&Context) -> Color,
+        set: fn(&mut Context, Color),
+    ) -> Self {
+        Self {
+            label,
+            kind: EntryKind::Color { palette, get, set },
+        }
+    }
+
+    /// Changes the value in `ctx` by one step. Options wrap around, ranges stop
+    /// at their ends.
+    pub fn step(&self, ctx: &mut Context, forward: bool) {
+        let cycle = |index: usize, count: usize| match forward {
+            true => (index + 1) % count,
+            false => (index + count - 1) % count,
+        };
+        match &self.kind {
+            EntryKind::Bool { get, set } => set(ctx, !get(ctx)),
+            EntryKind::Choice {
+                count, get, set, ..
+            } => set(ctx, cycle(get(ctx), *count)),
+            EntryKind::Range {
+                range,
+                step,
+                get,
+                set,
+                ..
+            } => {
+                let step = if forward { *step } else { -step };
+                set(ctx, (get(ctx) + step).clamp(*range.start(), *range.end()));
+            }
+            EntryKind::Color { palette, get, set } => {
+                let current = get(ctx);
+                let mut index = match palette.iter().position(|&(_, color)| color == current) {
+                    Some(index) => cycle(index, palette.len()),
+                    // A colour from the theme rather than the palette.
+                    None if forward => 0,
+                    None => palette.len() - 1,
+                };
+                // Skip the background, it would hide whatever uses the colour.
+                for _ in 1..palette.len() {
+                    if palette[index].1 != ctx.theme.background {
+                        break;
+                    }
+                    index = cycle(index, palette.len());
+                }
+                set(ctx, palette[index].1);
+            }
+        }
+    }
+
+    /// The value in `ctx` as text, e.g. `80%` or `on`.
+    pub fn value(&self, ctx: &Context) -> String {
+        match &self.kind {
+            EntryKind::Bool { get, .. } => if get(ctx) { "on" } else { "off" }.to_string(),
+            EntryKind::Choice { name, get, .. } => name(get(ctx)).to_string(),
+            EntryKind::Range { unit, get, .. } => format!("{}{unit}", get(ctx)),
+            EntryKind::Color { palette, get, .. } => {
+                let current = get(ctx);
+                match palette.iter().find(|&&(_, color)| color == current) {
+                    Some((name, _)) => name.to_string(),
+                    None => current.to_string(),
+                }
+            }
+        }
+    }
+
+    /// How far into its range the value is, from 0 to 1, for ranges only.
+    fn ratio(&self, ctx: &Context) -> Option<f64> {
+        let EntryKind::Range { range, get, .. } = &self.kind else {
+            return None;
+        };
+        let span = f64::from(range.end() - range.start());
+        Some(f64::from(get(ctx) - range.start()) / span)
+    }
+
+    /// A swatch in the colour of colour entries.
+    fn swatch(&self, ctx: &Context) -> Option<Span<'static>> {
+        let EntryKind::Color { get, .. } = &self.kind else {
+            return None;
+        };
+        Some(Span::raw("██ ").fg(get(ctx)))
+    }
+}
+
+/// Entries for the settings kept in the [`Settings`](crate::settings::Settings) record.
+pub fn demo_entries() -> Vec<Entry> {
+    vec![
+        Entry::range(
+            "Brightness",
+            10..=100,
+            10,
+            "%",
+            |ctx| ctx.power.config.brightness.into(),
+            |ctx, percent| ctx.power.config.brightness = percent as u8,
+        ),
+        Entry::choice(
+            "Theme",
+            THEMES.len(),
+            |index| THEMES[index].name,
+            |ctx| {
+                let name = ctx.theme.name;
+                THEMES
+                    .iter()
+                    .position(|theme| theme.name == name)
+                    .unwrap_or(0)
+            },
+            |ctx, index| ctx.theme = THEMES[index],
+        ),
+        Entry::color(
+            "Accent",
+            &ACCENTS,
+            |ctx| ctx.theme.accent,
+            |ctx, color| ctx.theme.accent = color,
+        ),
+        Entry::bool(
+            "Carousel",
+            |ctx| ctx.carousel.config.enabled,
+            |ctx, on| ctx.carousel.config.enabled = on,
+        ),
+        Entry::range(
+            "Carousel dwell",
+            5..=120,
+            5,
+            "s",
+            |ctx| ctx.carousel.config.dwell.as_secs() as i32,
+            |ctx, seconds| ctx.carousel.config.dwell = Duration::from_secs(seconds as u64),
+        ),
+        Entry::bool(
+            "Shuffle",
+            |ctx| ctx.carousel.config.shuffle,
+            |ctx, on| ctx.carousel.config.shuffle = on,
+        ),
+    ]
+}
+
+/// Lists settings and edits the selected one with S1, showing every change right away.
+pub struct SettingsApp {
+    entries: Vec<Entry>,
+    selected: usize,
+    editing: bool,
+}
+
+impl SettingsApp {
+    pub fn new() -> Self {
+        Self::with_entries(demo_entries())
+    }
+
+    pub fn with_entries(entries: Vec<Entry>) -> Self {
+        assert!(!entries.is_empty(), "SettingsApp needs at least one entry");
+        Self {
+            entries,
+            selected: 0,
+            editing: false,
+        }
+    }
+}
+
+impl Default for SettingsApp {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Screen for SettingsApp {
+    fn name(&self) -> &'static str {
+        "settings"
+    }
+
+    fn init(&mut self, _ctx: &mut Context) {
+        self.editing = false;
+    }
+
+    fn handle_event(&mut self, event: InputEvent, ctx: &mut Context) -> Action {
+        let entry = &self.entries[self.selected];
+        match (self.editing, event) {
+            (true, InputEvent::ShortPress | InputEvent::Repeat) => entry.step(ctx, true),
+            (true, InputEvent::DoublePress) => entry.step(ctx, false),
+            (true, InputEvent::LongPress) => self.editing = false,
+            (false, InputEvent::ShortPress | InputEvent::Repeat) => {
+                self.selected = (self.selected + 1) % self.entries.len();
+            }
+            (false, InputEvent::LongPress) => self.editing = true,
+            (false, InputEvent::DoublePress) => return Action::PreviousScreen,
+        }
+        Action::Stay
+    }
+
+    fn hints(&self) -> &'static str {
+        if self.editing {
+            "[S1/2x] change [hold] done"
+        } else {
+            "[S1] next [hold] edit [2x] back"
+        }
+    }
+
+    fn draw(&self, frame: &mut Frame, area: Rect, ctx: &Context) {
+        let theme = &ctx.theme;
+        let block = Block::bordered()
+            .border_set(symbols::border::PROPORTIONAL_TALL)
+            .padding(Padding::horizontal(1))
+            .border_style(theme.border);
+        let rows = self.entries.iter().map(|entry| {
+            let mut value: Vec<Span> = entry.swatch(ctx).into_iter().collect();
+            value.push(entry.value(ctx).into());
+            Row::new([
+                Cell::from(entry.label),
+                Cell::from(Line::from(value).right_aligned()),
+            ])
+        });
+        let table = Table::new(rows, [Constraint::Min(0), Constraint::Length(VALUE_WIDTH)])
+            .block(block)
+            .row_highlight_style(theme.highlighted());
+        let mut state = TableState::default().with_selected(Some(self.selected));
+        frame.render_stateful_widget(table, area, &mut state);
+
+        if self.editing {
+            self.draw_editor(frame, area, ctx);
+        }
+    }
+}
+
+impl SettingsApp {
+    /// Popup with the value of the selected entry being edited.
+    fn draw_editor(&self, frame: &mut Frame, area: Rect, ctx: &Context) {
+        let entry = &self.entries[self.selected];
+        let ratio = entry.ratio(ctx);
+        let style = Style::default()
+            .fg(ctx.theme.background)
+            .bg(ctx.theme.accent);
+        let height = if ratio.is_some() { 4 } else { 3 };
+        let area = center(area, Constraint::Length(26), Constraint::Length(height));
+        let block = Block::bordered().border_style(style).title(entry.label);
+
+        let mut lines = vec![Line::from(format!("< {} >", entry.value(ctx)))];
+        if let Some(ratio) = ratio {
+            let width = usize::from(block.inner(area).width);
+            let filled = (ratio * width as f64).round() as usize;
+            lines.push(Line::from("█".repeat(filled) + &"─".repeat(width - filled)));
+        }
+        let popup = Paragraph::new(lines).centered().block(block).style(style);
+        frame.render_widget(Clear, area);
+        frame.render_widget(popup, area);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::theme::{DARK, LIGHT};
+
+    #[test]
+    fn edits_the_selected_entry_live() {
+        let mut ctx = Context::default();
+        let mut app = SettingsApp::new();
+        app.handle_event(InputEvent::LongPress, &mut ctx);
+        app.handle_event(InputEvent::DoublePress, &mut ctx);
+        assert_eq!(ctx.power.config.brightness, 90);
+        for _ in 0..3 {
+            app.handle_event(InputEvent::ShortPress, &mut ctx);
+        }
+        assert_eq!(ctx.power.config.brightness, 100);
+        app.handle_event(InputEvent::LongPress, &mut ctx);
+
+        app.handle_event(InputEvent::ShortPress, &mut ctx);
+        app.handle_event(InputEvent::LongPress, &mut ctx);
+        app.handle_event(InputEvent::ShortPress, &mut ctx);
+        assert_eq!(ctx.theme, LIGHT);
+        assert_eq!(app.hints(), "[S1/2x] change [hold] done");
+        app.handle_event(InputEvent::LongPress, &mut ctx);
+        assert_eq!(
+            app.handle_event(InputEvent::DoublePress, &mut ctx),
+            Action::PreviousScreen
+        );
+    }
+
+    #[test]
+    fn steps_through_choices_and_colours() {
+        let mut ctx = Context::default();
+        let entries = demo_entries();
+        let find = |label| entries.iter().find(|entry| entry.label == label).unwrap();
+
+        let theme = find("Theme");
+        theme.step(&mut ctx, false);
+        assert_eq!(theme.value(&ctx), THEMES[THEMES.len() - 1].name);
+        theme.step(&mut ctx, true);
+        assert_eq!(theme.value(&ctx), "dark");
+
+        // The light theme's accent is in the palette, the high contrast one's isn't.
+        let accent = find("Accent");
+        ctx.theme = LIGHT;
+        assert_eq!(accent.value(&ctx), "blue");
+        // White is skipped on the light theme's white background.
+        accent.step(&mut ctx, true);
+        assert_eq!(accent.value(&ctx), "yellow");
+        accent.step(&mut ctx, false);
+        assert_eq!(accent.value(&ctx), "blue");
+        ctx.theme.accent = Color::Rgb(255, 255, 0);
+        assert_eq!(accent.value(&ctx), "#FFFF00");
+        accent.step(&mut ctx, false);
+        assert_eq!(accent.value(&ctx), "blue");
+        ctx.theme = DARK;
+        ctx.theme.accent = Color::Rgb(255, 255, 0);
+        accent.step(&mut ctx, false);
+        assert_eq!(ctx.theme.accent, Color::White);
+
+        let carousel = find("Carousel");
+        carousel.step(&mut ctx, true);
+        assert_eq!(carousel.value(&ctx), "on");
+    }
+}
@@ -0,0 +1,531 @@
+use crate::app::App;
+use crate::battery::ChargeState;
+use crate::carousel::{PlaylistError, format_playlist, parse_playlist};
+use crate::clock::{TimeError, TimeSource, format_offset, parse_offset, parse_time};
+use crate::power::SleepMode;
+use crate::screen::Context;
+use crate::screenshot::ScreenshotFormat;
+use crate::theme::Theme;
+use crate::transition::parse_transition;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+const HELP: &str = "\
+help                  this list
+screens               list the screens
+screen <name|number>  show a screen
+next, prev            show the next or previous screen
+brightness <0-100>    set the backlight level
+set [<key> <value>]   list or change a setting
+battery               show the battery reading
+time                  show the time
+time set <when>       set the time, e.g. 2025-05-23 14:05
+time zone <offset>    set the UTC offset, e.g. +02:00
+screenshot [format]   print the next frame as text, ansi or bmp";
+
+/// One line typed on the console.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    Help,
+    Screens,
+    Screen(String),
+    Next,
+    Previous,
+    Brightness(u8),
+    /// Lists the settings without a key.
+    Set(Option<(String, String)>),
+    Battery,
+    Time,
+    TimeSet(String),
+    TimeZone(String),
+    Screenshot(ScreenshotFormat),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShellError {
+    UnknownCommand(String),
+    /// The arguments don't match the usage shown.
+    Usage(&'static str),
+    UnknownScreen(String),
+    UnknownSetting(String),
+    InvalidValue {
+        key: &'static str,
+        expected: &'static str,
+    },
+    Time(TimeError),
+    Playlist(PlaylistError),
+}
+
+impl fmt::Display for ShellError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ShellError::UnknownCommand(command) => {
+                write!(f, "unknown command `{command}`, try `help`")
+            }
+            ShellError::Usage(usage) => write!(f, "usage: {usage}"),
+            ShellError::UnknownScreen(screen) => {
+                write!(f, "no screen `{screen}`, try `screens`")
+            }
+            ShellError::UnknownSetting(key) => write!(f, "no setting `{key}`, try `set`"),
+            ShellError::InvalidValue { key, expected } => {
+                write!(f, "{key} must be {expected}")
+            }
+            ShellError::Time(error) => error.fmt(f),
+            ShellError::Playlist(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for ShellError {}
+
+impl From<TimeError> for ShellError {
+    fn from(error: TimeError) -> Self {
+        ShellError::Time(error)
+    }
+}
+
+impl From<PlaylistError> for ShellError {
+    fn from(error: PlaylistError) -> Self {
+        ShellError::Playlist(error)
+    }
+}
+
+/// Parses a command line, `None` for blank lines and `#` comments.
+pub fn parse(line: &str) -> Result<Option<Command>, ShellError> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return Ok(None);
+    }
+    let (name, rest) = split_word(line);
+    let command = match (name, rest) {
+        ("help" | "?", _) => Command::Help,
+        ("screens", rest) => no_arguments(rest, Command::Screens, "screens")?,
+        ("screen", "") => return Err(ShellError::Usage("screen <name|number>")),
+        ("screen", screen) => Command::Screen(screen.to_string()),
+        ("next", rest) => no_arguments(rest, Command::Next, "next")?,
+        ("prev" | "previous", rest) => no_arguments(rest, Command::Previous, "prev")?,
+        ("brightness", level) => Command::Brightness(parse_percent("brightness", level)?),
+        ("set", "") => Command::Set(None),
+        ("set", rest) => match split_word(rest) {
+            (_, "") => return Err(ShellError::Usage("set <key> <value>")),
+            (key, value) => Command::Set(Some((key.to_string(), value.to_string()))),
+        },
+        ("battery", rest) => no_arguments(rest, Command::Battery, "battery")?,
+        ("time", "") => Command::Time,
+        ("time", rest) => match split_word(rest) {
+            ("set", "") => return Err(ShellError::Usage("time set <when>")),
+            ("set", when) => Command::TimeSet(when.to_string()),
+            ("zone", "") => return Err(ShellError::Usage("time zone <offset>")),
+            ("zone", offset) => Command::TimeZone(offset.to_string()),
+            _ => return Err(ShellError::Usage("time [set <when> | zone <offset>]")),
+        },
+        ("screenshot", "") => Command::Screenshot(ScreenshotFormat::default()),
+        ("screenshot", format) => Command::Screenshot(
+            ScreenshotFormat::from_name(format)
+                .ok_or(ShellError::Usage("screenshot [text|ansi|bmp]"))?,
+        ),
+        (name, _) => return Err(ShellError::UnknownCommand(name.to_string())),
+    };
+    Ok(Some(command))
+}
+
+/// Runs `command` against the app and returns what to print.
+pub fn execute(app: &mut App, command: Command) -> Result<String, ShellError> {
+    let names = app.screen_names();
+    let reply = match command {
+        Command::Help => HELP.to_string(),
+        Command::Screens => names
+            .iter()
+            .enumerate()
+            .map(|(index, name)| {
+                let marker = if index == app.current() { '*' } else { ' ' };
+                format!("{marker}{} {name}", index + 1)
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Command::Screen(screen) => {
+            let index = match screen.parse::<usize>() {
+                Ok(number) => number.checked_sub(1).filter(|&index| index < names.len()),
+                Err(_) => names.iter().position(|&name| name == screen),
+            };
+            let index = index.ok_or(ShellError::UnknownScreen(screen))?;
+            app.show(index);
+            format!("showing {}", names[index])
+        }
+        Command::Next | Command::Previous => {
+            let step = if command == Command::Next {
+                1
+            } else {
+                names.len() - 1
+            };
+            let index = (app.current() + step) % names.len();
+            app.show(index);
+            format!("showing {}", names[index])
+        }
+        Command::Brightness(level) => set_setting(app, "brightness", &level.to_string())?,
+        Command::Set(None) => settings(app.context()),
+        Command::Set(Some((key, value))) => set_setting(app, &key, &value)?,
+        Command::Battery => battery(app.context()),
+        Command::Time => time(app.context()),
+        Command::TimeSet(when) => {
+            let clock = &mut app.context_mut().clock;
+            let utc = parse_time(&when, clock.offset())?;
+            clock.set(utc, TimeSource::Serial, Instant::now());
+            time(app.context())
+        }
+        Command::TimeZone(offset) => {
+            app.context_mut().clock.set_offset(parse_offset(&offset)?);
+            time(app.context())
+        }
+        Command::Screenshot(format) => {
+            app.request_screenshot(format);
+            format!("capturing the next frame as {}", format.name())
+        }
+    };
+    Ok(reply)
+}
+
+/// Parses and runs one line, returning the reply or the error message.
+pub fn run_line(app: &mut App, line: &str) -> Option<String> {
+    match parse(line) {
+        Ok(Some(command)) => match execute(app, command) {
+            Ok(reply) => Some(reply),
+            Err(error) => Some(format!("error: {error}")),
+        },
+        Ok(None) => None,
+        Err(error) => Some(format!("error: {error}")),
+    }
+}
+
+/// Keys accepted by `set`, listed in this order.
+const SETTINGS: [&str; 16] = [
+    "brightness",
+    "dimmed_brightness",
+    "dim_after",
+    "off_after",
+    "sleep_after",
+    "sleep_mode",
+    "timezone",
+    "theme",
+    "carousel",
+    "playlist",
+    "shuffle",
+    "dwell",
+    "resume_after",
+    "transition_next",
+    "transition_prev",
+    "transition_jump",
+];
+
+fn settings(ctx: &Context) -> String {
+    SETTINGS
+        .iter()
+        .map(|key| format!("{key} = {}", setting(ctx, key).unwrap_or_default()))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn setting(ctx: &Context, key: &str) -> Option<String> {
+    let config = &ctx.power.config;
+    let value = match key {
+        "brightness" => config.brightness.to_string(),
+        "dimmed_brightness" => config.dimmed_brightness.to_string(),
+        "dim_after" => config.dim_after.as_secs().to_string(),
+        "off_after" => config.off_after.as_secs().to_string(),
+        "sleep_after" => config.sleep_after.as_secs().to_string(),
+        "sleep_mode" => match config.sleep_mode {
+            SleepMode::Light => "light".to_string(),
+            SleepMode::Deep => "deep".to_string(),
+        },
+        "timezone" => format_offset(ctx.clock.offset()),
+        "theme" => ctx.theme.name.to_string(),
+        "carousel" => on_off(ctx.carousel.config.enabled),
+        "playlist" => format_playlist(&ctx.carousel.config.playlist),
+        "shuffle" => on_off(ctx.carousel.config.shuffle),
+        "dwell" => ctx.carousel.config.dwell.as_secs().to_string(),
+        "resume_after" => ctx.carousel.config.resume_after.as_secs().to_string(),
+        "transition_next" => ctx.transitions.next.to_string(),
+        "transition_prev" => ctx.transitions.previous.to_string(),
+        "transition_jump" => ctx.transitions.jump.to_string(),
+        _ => return None,
+    };
+    Some(value)
+}
+
+fn set_setting(app: &mut App, key: &str, value: &str) -> Result<String, ShellError> {
+    if key == "playlist" {
+        let mut carousel = app.context().carousel.config.clone();
+        carousel.playlist = parse_playlist(value)?;
+        app.set_carousel(carousel)?;
+        let playlist = setting(app.context(), key).unwrap_or_default();
+        return Ok(format!("{key} = {playlist}"));
+    }
+    let ctx = app.context_mut();
+    let config = &mut ctx.power.config;
+    let transition = |key| {
+        parse_transition(value).ok_or(ShellError::InvalidValue {
+            key,
+            expected: "cut, slide-left, slide-right, wipe or dissolve[:<ms>]",
+        })
+    };
+    let seconds = |key| {
+        value
+            .parse()
+            .map(Duration::from_secs)
+            .map_err(|_| ShellError::InvalidValue {
+                key,
+                expected: "a number of seconds",
+            })
+    };
+    match key {
+        "brightness" => config.brightness = parse_percent("brightness", value)?,
+        "dimmed_brightness" => {
+            config.dimmed_brightness = parse_percent("dimmed_brightness", value)?
+        }
+        "dim_after" => config.dim_after = seconds("dim_after")?,
+        "off_after" => config.off_after = seconds("off_after")?,
+        "sleep_after" => config.sleep_after = seconds("sleep_after")?,
+        "sleep_mode" => {
+            config.sleep_mode = match value {
+                "light" => SleepMode::Light,
+                "deep" => SleepMode::Deep,
+                _ => {
+                    return Err(ShellError::InvalidValue {
+                        key: "sleep_mode",
+                        expected: "light or deep",
+                    });
+                }
+            }
+        }
+        "timezone" => ctx.clock.set_offset(parse_offset(value)?),
+        "theme" => {
+            ctx.theme = *Theme::by_name(value).ok_or(ShellError::InvalidValue {
+                key: "theme",
+                expected: "dark, light, high-contrast or colorblind",
+            })?
+        }
+        "carousel" => ctx.carousel.config.enabled = parse_on_off("carousel", value)?,
+        "shuffle" => ctx.carousel.config.shuffle = parse_on_off("shuffle", value)?,
+        "dwell" => ctx.carousel.config.dwell = seconds("dwell")?,
+        "resume_after" => ctx.carousel.config.resume_after = seconds("resume_after")?,
+        "transition_next" => ctx.transitions.next = transition("transition_next")?,
+        "transition_prev" => ctx.transitions.previous = transition("transition_prev")?,
+        "transition_jump" => ctx.transitions.jump = transition("transition_jump")?,
+        _ => return Err(ShellError::UnknownSetting(key.to_string())),
+    }
+    let value = setting(ctx, key).unwrap_or_default();
+    Ok(format!("{key} = {value}"))
+}
+
+fn battery(ctx: &Context) -> String {
+    let Some(state) = ctx.battery.state() else {
+        return "no battery reading".to_string();
+    };
+    let charge = match state.charge {
+        ChargeState::Unknown => "measuring",
+        ChargeState::Charging => "charging",
+        ChargeState::Discharging => "discharging",
+    };
+    let calibration = &ctx.calibration;
+    format!(
+        "{:.3}V {}% {charge}\ndivider {:.2}, gain {:.3}, offset {}mV",
+        f32::from(state.millivolts) / 1000.0,
+        state.percent,
+        calibration.divider,
+        calibration.gain,
+        calibration.offset_millivolts,
+    )
+}
+
+fn time(ctx: &Context) -> String {
+    let clock = &ctx.clock;
+    match clock.now() {
+        Some(now) => format!(
+            "{} {:02}:{:02}:{:02} UTC{} ({})",
+            now.date(),
+            now.hour(),
+            now.minute(),
+            now.second(),
+            format_offset(clock.offset()),
+            clock.source()
+        ),
+        None => "time not set".to_string(),
+    }
+}
+
+fn split_word(text: &str) -> (&str, &str) {
+    match text.split_once(char::is_whitespace) {
+        Some((word, rest)) => (word, rest.trim_start()),
+        None => (text, ""),
+    }
+}
+
+fn parse_percent(key: &'static str, value: &str) -> Result<u8, ShellError> {
+    value
+        .parse()
+        .ok()
+        .filter(|&percent| percent <= 100)
+        .ok_or(ShellError::InvalidValue {
+            key,
+            expected: "0 to 100",
+        })
+}
+
+fn on_off(enabled: bool) -> String {
+    if enabled { "on" } else { "off" }.to_string()
+}
+
+fn parse_on_off(key: &'static str, value: &str) -> Result<bool, ShellError> {
+    match value {
+        "on" => Ok(true),
+        "off" => Ok(false),
+        _ => Err(ShellError::InvalidValue {
+            key,
+            expected: "on or off",
+        }),
+    }
+}
+
+fn no_arguments(rest: &str, command: Command, usage: &'static str) -> Result<Command, ShellError> {
+    if rest.is_empty() {
+        Ok(command)
+    } else {
+        Err(ShellError::Usage(usage))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::demo_screens;
+    use crate::chart::ChartApp;
+    use crate::metrics::{MetricsSource, SystemMetrics};
+
+    struct NoMetrics;
+
+    impl MetricsSource for NoMetrics {
+        fn sample(&mut self) -> SystemMetrics {
+            SystemMetrics::default()
+        }
+    }
+
+    fn app() -> App {
+        App::new(demo_screens(Box::new(NoMetrics), ChartApp::new()))
+    }
+
+    #[test]
+    fn parses_commands() {
+        assert_eq!(parse("  # comment"), Ok(None));
+        assert_eq!(parse("next"), Ok(Some(Command::Next)));
+        assert_eq!(
+            parse("screen  chart "),
+            Ok(Some(Command::Screen("chart".into())))
+        );
+        assert_eq!(parse("brightness 40"), Ok(Some(Command::Brightness(40))));
+        assert_eq!(
+            parse("set sleep_mode deep"),
+            Ok(Some(Command::Set(Some((
+                "sleep_mode".into(),
+                "deep".into()
+            )))))
+        );
+        assert_eq!(
+            parse("time set 2025-05-23 14:05"),
+            Ok(Some(Command::TimeSet("2025-05-23 14:05".into())))
+        );
+        assert_eq!(
+            parse("screenshot bmp"),
+            Ok(Some(Command::Screenshot(ScreenshotFormat::Bmp)))
+        );
+    }
+
+    #[test]
+    fn rejects_bad_input() {
+        assert_eq!(
+            parse("reboot"),
+            Err(ShellError::UnknownCommand("reboot".into()))
+        );
+        assert_eq!(
+            parse("screen"),
+            Err(ShellError::Usage("screen <name|number>"))
+        );
+        assert_eq!(parse("next 2"), Err(ShellError::Usage("next")));
+        assert!(matches!(
+            parse("brightness 120"),
+            Err(ShellError::InvalidValue { .. })
+        ));
+        assert_eq!(
+            parse("set brightness"),
+            Err(ShellError::Usage("set <key> <value>"))
+        );
+        assert_eq!(
+            parse("screenshot png"),
+            Err(ShellError::Usage("screenshot [text|ansi|bmp]"))
+        );
+    }
+
+    #[test]
+    fn switches_screens() {
+        let mut app = app();
+        assert_eq!(run_line(&mut app, "screen chart").unwrap(), "showing chart");
+        assert_eq!(app.current(), 2);
+        for _ in 0..3 {
+            run_line(&mut app, "prev");
+        }
+        assert_eq!(app.screen_names()[app.current()], "settings");
+        run_line(&mut app, "screen 2");
+        assert_eq!(app.current(), 1);
+        assert_eq!(
+            run_line(&mut app, "screen 9").unwrap(),
+            "error: no screen `9`, try `screens`"
+        );
+    }
+
+    #[test]
+    fn changes_settings() {
+        let mut app = app();
+        assert_eq!(
+            run_line(&mut app, "brightness 40").unwrap(),
+            "brightness = 40"
+        );
+        assert_eq!(app.context().power.config.brightness, 40);
+        run_line(&mut app, "set dim_after 5");
+        assert_eq!(app.context().power.config.dim_after, Duration::from_secs(5));
+        assert_eq!(
+            run_line(&mut app, "set sleep_mode never").unwrap(),
+            "error: sleep_mode must be light or deep"
+        );
+        assert_eq!(
+            run_line(&mut app, "set timezone -05:30").unwrap(),
+            "timezone = -05:30"
+        );
+        assert_eq!(
+            run_line(&mut app, "set playlist logo:5, chart").unwrap(),
+            "playlist = logo:5,chart"
+        );
+        assert_eq!(
+            run_line(&mut app, "set playlist clock").unwrap(),
+            "error: no screen `clock`"
+        );
+        assert_eq!(
+            run_line(&mut app, "set transition_prev wipe:150").unwrap(),
+            "transition_prev = wipe:150"
+        );
+        run_line(&mut app, "set carousel on");
+        assert!(app.context().carousel.config.enabled);
+        assert_eq!(
+            run_line(&mut app, "set theme light").unwrap(),
+            "theme = light"
+        );
+        assert_eq!(app.context().theme, crate::theme::LIGHT);
+    }
+
+    #[test]
+    fn sets_time() {
+        let mut app = app();
+        assert_eq!(run_line(&mut app, "time").unwrap(), "time not set");
+        let reply = run_line(&mut app, "time set 2025-05-23 14:05Z").unwrap();
+        assert!(reply.starts_with("2025-05-23 14:05:0"), "{reply}");
+        assert_eq!(app.context().clock.source(), TimeSource::Serial);
+    }
+}
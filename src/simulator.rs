@@ -0,0 +1,289 @@
+mod framebuffer;
+mod metrics;
+
+use crossterm::cursor::{self, MoveTo, MoveToNextLine};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use crossterm::style::{self, Print, ResetColor, SetBackgroundColor, SetForegroundColor};
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+use embedded_graphics::pixelcolor::{Rgb565, Rgb888};
+use embedded_graphics::prelude::{RgbColor, Size};
+use framebuffer::FrameBuffer;
+use metrics::HostMetrics;
+use mousefood::prelude::*;
+use mousefood::ratatui::symbols::Marker;
+use mousefood_esp32_demo::app::{App, demo_screens};
+use mousefood_esp32_demo::board::BoardProfile;
+use mousefood_esp32_demo::calibration::Calibration;
+use mousefood_esp32_demo::carousel::CarouselConfig;
+use mousefood_esp32_demo::chart::{ChartApp, Series};
+use mousefood_esp32_demo::clock::TimeSource;
+use mousefood_esp32_demo::input::{InputEvent, InputSource, ScriptedInput};
+use mousefood_esp32_demo::overlay::LowBatteryOverlay;
+use mousefood_esp32_demo::power::PowerState;
+use mousefood_esp32_demo::screenshot;
+use mousefood_esp32_demo::settings::{FileBackend, Settings, SettingsStore};
+use mousefood_esp32_demo::shell;
+use mousefood_esp32_demo::source::CsvSource;
+use std::env;
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufWriter, IsTerminal, Write};
+use std::time::{Duration, Instant, SystemTime};
+
+/// There is no ADC on the host, so the voltage screen shows a fixed reading.
+const BATTERY_MILLIVOLTS: u16 = 3900;
+/// Where the simulator keeps its settings, in the working directory.
+const SETTINGS_FILE: &str = "simulator-settings.txt";
+
+pub fn run() {
+    // Piped commands, e.g. `echo "screen chart" | cargo run`, go to the shell
+    // without a display, the same way they would over the serial console.
+    if !io::stdin().is_terminal() {
+        run_shell().unwrap();
+        return;
+    }
+
+    let mut stdout = io::stdout();
+    terminal::enable_raw_mode().unwrap();
+    execute!(
+        stdout,
+        EnterAlternateScreen,
+        cursor::Hide,
+        Clear(ClearType::All)
+    )
+    .unwrap();
+
+    let result = simulate(&mut stdout);
+
+    execute!(stdout, ResetColor, cursor::Show, LeaveAlternateScreen).unwrap();
+    terminal::disable_raw_mode().unwrap();
+    result.unwrap();
+}
+
+fn run_shell() -> io::Result<()> {
+    let board = BoardProfile::selected();
+    let size = display_size(board);
+    let mut display = FrameBuffer::new(size);
+    let mut terminal = Terminal::new(EmbeddedBackend::new(&mut display, Default::default()))?;
+
+    let mut app = new_app(board);
+    let mut settings = restore_settings(&mut app);
+    let adc_millivolts = adc_millivolts(&app);
+    for line in io::stdin().lock().lines() {
+        app.context_mut()
+            .push_battery_sample(adc_millivolts, Instant::now());
+        if let Some(reply) = shell::run_line(&mut app, &line?) {
+            println!("{reply}");
+        }
+        // Draw a frame after every command, so requested screenshots get taken.
+        app.frame(&mut terminal, &mut ScriptedInput::new())?;
+        if let Some((format, buffer)) = app.take_screenshot() {
+            screenshot::export(format, &buffer, size, &mut io::stdout().lock())?;
+        }
+        if let Err(error) = settings.update(Settings::from_app(&app), Instant::now()) {
+            eprintln!("Can't save the settings: {error}");
+        }
+    }
+    settings.flush()
+}
+
+fn display_size(board: &BoardProfile) -> Size {
+    let (width, height) = board.resolution();
+    Size::new(width.into(), height.into())
+}
+
+fn new_app(board: &BoardProfile) -> App {
+    let mut app = App::new(demo_screens(Box::new(HostMetrics::new()), chart()))
+        .with_overlay(Box::new(LowBatteryOverlay::default()));
+    app.context_mut().calibration = Calibration::for_board(board.battery);
+    app.set_carousel(CarouselConfig::selected())
+        .expect("Invalid DEMO_PLAYLIST");
+    app.context_mut()
+        .clock
+        .set(SystemTime::now().into(), TimeSource::System, Instant::now());
+    app
+}
+
+/// Plots the recording given as `<file.csv> <column> [unit]` arguments instead of
+/// the sine waves, e.g. `cargo run -- discharge.csv volts V`.
+fn chart() -> ChartApp {
+    let mut args = env::args().skip(1);
+    if let (Some(path), Some(column)) = (args.next(), args.next()) {
+        match read_recording(&path, &column) {
+            Ok(recording) => {
+                let unit = args.next().unwrap_or_default().leak();
+                let series = Series::new(column, Marker::Braille, recording);
+                return ChartApp::with_series(vec![series]).with_unit(unit);
+            }
+            Err(error) => eprintln!("{path}: {error}, plotting the sine waves"),
+        }
+    }
+    ChartApp::new()
+}
+
+fn read_recording(path: &str, column: &str) -> Result<CsvSource, Box<dyn Error>> {
+    let text = fs::read_to_string(path)?;
+    Ok(CsvSource::parse(&text, column)?)
+}
+
+/// Applies the settings saved by an earlier run to `app`.
+fn restore_settings(app: &mut App) -> SettingsStore<FileBackend> {
+    let mut settings = SettingsStore::new(FileBackend::new(SETTINGS_FILE));
+    match settings.load() {
+        Ok(saved) => saved.apply(app),
+        Err(error) => eprintln!("{error}, using the defaults"),
+    }
+    settings
+}
+
+/// Reading at the ADC pin for [`BATTERY_MILLIVOLTS`] through the board's divider.
+fn adc_millivolts(app: &App) -> u16 {
+    (f32::from(BATTERY_MILLIVOLTS) / app.context().calibration.divider) as u16
+}
+
+fn simulate(out: &mut impl Write) -> io::Result<()> {
+    let board = BoardProfile::selected();
+    let size = display_size(board);
+    let framebuffer = FrameBuffer::new(size);
+    let mut display = framebuffer.clone();
+    let backend = EmbeddedBackend::new(&mut display, Default::default());
+    let mut terminal = Terminal::new(backend)?;
+
+    let mut app = new_app(board);
+    let mut settings = restore_settings(&mut app);
+    let adc_millivolts = adc_millivolts(&app);
+
+    let mut keyboard = Keyboard::default();
+    let mut previous = (Vec::new(), 0);
+    let mut screenshots = 0;
+    loop {
+        app.context_mut()
+            .push_battery_sample(adc_millivolts, Instant::now());
+        app.frame(&mut terminal, &mut keyboard)?;
+        if let Err(error) = settings.update(Settings::from_app(&app), Instant::now()) {
+            eprintln!("Can't save the settings: {error}");
+        }
+        if keyboard.quit || app.context().power.state() == PowerState::Shutdown {
+            return settings.flush();
+        }
+        if app.context().power.state() == PowerState::Sleep {
+            if let Err(error) = settings.flush() {
+                eprintln!("Can't save the settings: {error}");
+            }
+            present(&framebuffer.pixels(), size, 0, out)?;
+            // The host can't sleep, so wait for a key the way the board waits for S1.
+            while keyboard.wait(Duration::from_secs(60)).is_none() {
+                if keyboard.quit {
+                    return settings.flush();
+                }
+            }
+            app.context_mut().power.woke_up(Instant::now());
+            continue;
+        }
+
+        let pixels = framebuffer.pixels();
+        if keyboard.screenshot {
+            keyboard.screenshot = false;
+            screenshots += 1;
+            write_png(&format!("screenshot-{screenshots}.png"), &pixels, size)?;
+        }
+        let backlight = app.context().power.backlight();
+        if (&pixels, backlight) != (&previous.0, previous.1) {
+            present(&pixels, size, backlight, out)?;
+            previous = (pixels, backlight);
+        }
+    }
+}
+
+/// Maps keys to S1 gestures, since terminals don't report how long a key is held,
+/// plus a few simulator-only keys.
+#[derive(Default)]
+struct Keyboard {
+    quit: bool,
+    screenshot: bool,
+}
+
+impl InputSource for Keyboard {
+    fn wait(&mut self, timeout: Duration) -> Option<InputEvent> {
+        let deadline = Instant::now() + timeout;
+        while event::poll(deadline.saturating_duration_since(Instant::now())).unwrap_or(false) {
+            let Ok(Event::Key(key)) = event::read() else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match key.code {
+                KeyCode::Char(' ') | KeyCode::Enter => return Some(InputEvent::ShortPress),
+                KeyCode::Char('l') => return Some(InputEvent::LongPress),
+                KeyCode::Char('d') => return Some(InputEvent::DoublePress),
+                KeyCode::Char('r') => return Some(InputEvent::Repeat),
+                KeyCode::Char('p') => self.screenshot = true,
+                KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+                _ => continue,
+            }
+            // Simulator keys are handled by the caller right after this frame.
+            return None;
+        }
+        None
+    }
+}
+
+/// Draws the pixels with half-block characters, two pixel rows per terminal line,
+/// darkened to the backlight level in percent.
+fn present(pixels: &[Rgb565], size: Size, backlight: u8, out: &mut impl Write) -> io::Result<()> {
+    let width = size.width as usize;
+    let height = size.height as usize;
+    queue!(out, MoveTo(0, 0))?;
+    for y in (0..height).step_by(2) {
+        for x in 0..width {
+            let top = pixels[y * width + x];
+            let bottom = if y + 1 < height {
+                pixels[(y + 1) * width + x]
+            } else {
+                Rgb565::BLACK
+            };
+            queue!(
+                out,
+                SetForegroundColor(terminal_color(top, backlight)),
+                SetBackgroundColor(terminal_color(bottom, backlight)),
+                Print('▀'),
+            )?;
+        }
+        queue!(out, ResetColor, MoveToNextLine(1))?;
+    }
+    queue!(
+        out,
+        Print("[Space] press  [L] long  [D] double  [R] repeat  [P] save PNG  [Q] quit")
+    )?;
+    out.flush()
+}
+
+fn terminal_color(color: Rgb565, backlight: u8) -> style::Color {
+    let color = Rgb888::from(color);
+    let dim = |channel: u8| (u16::from(channel) * u16::from(backlight.min(100)) / 100) as u8;
+    style::Color::Rgb {
+        r: dim(color.r()),
+        g: dim(color.g()),
+        b: dim(color.b()),
+    }
+}
+
+fn write_png(path: &str, pixels: &[Rgb565], size: Size) -> io::Result<()> {
+    let mut encoder =
+        png::Encoder::new(BufWriter::new(File::create(path)?), size.width, size.height);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let data: Vec<u8> = pixels
+        .iter()
+        .flat_map(|&pixel| {
+            let pixel = Rgb888::from(pixel);
+            [pixel.r(), pixel.g(), pixel.b()]
+        })
+        .collect();
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(&data))
+        .map_err(io::Error::other)
+}
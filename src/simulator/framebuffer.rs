@@ -0,0 +1,57 @@
+use embedded_graphics::pixelcolor::Rgb565;
+use embedded_graphics::prelude::*;
+use std::cell::RefCell;
+use std::convert::Infallible;
+use std::rc::Rc;
+
+/// In-memory RGB565 display.
+///
+/// Clones share the same pixels, so one handle can be given to mousefood
+/// while another one reads back what was drawn.
+#[derive(Clone)]
+pub struct FrameBuffer {
+    size: Size,
+    pixels: Rc<RefCell<Vec<Rgb565>>>,
+}
+
+impl FrameBuffer {
+    pub fn new(size: Size) -> Self {
+        let len = (size.width * size.height) as usize;
+        Self {
+            size,
+            pixels: Rc::new(RefCell::new(vec![Rgb565::BLACK; len])),
+        }
+    }
+
+    /// Copy of the current pixels in row-major order.
+    pub fn pixels(&self) -> Vec<Rgb565> {
+        self.pixels.borrow().clone()
+    }
+}
+
+impl OriginDimensions for FrameBuffer {
+    fn size(&self) -> Size {
+        self.size
+    }
+}
+
+impl DrawTarget for FrameBuffer {
+    type Color = Rgb565;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let mut buffer = self.pixels.borrow_mut();
+        for Pixel(point, color) in pixels {
+            let Ok((x, y)) = <(u32, u32)>::try_from(point) else {
+                continue;
+            };
+            if x < self.size.width && y < self.size.height {
+                buffer[(y * self.size.width + x) as usize] = color;
+            }
+        }
+        Ok(())
+    }
+}
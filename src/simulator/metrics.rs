@@ -0,0 +1,25 @@
+use mousefood_esp32_demo::metrics::{MetricsSource, SystemMetrics};
+use std::time::Instant;
+
+/// The host has no FreeRTOS to ask, so apart from the uptime this reports
+/// [`SystemMetrics::typical`].
+pub struct HostMetrics {
+    started: Instant,
+}
+
+impl HostMetrics {
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+        }
+    }
+}
+
+impl MetricsSource for HostMetrics {
+    fn sample(&mut self) -> SystemMetrics {
+        SystemMetrics {
+            uptime: self.started.elapsed(),
+            ..SystemMetrics::typical()
+        }
+    }
+}
@@ -0,0 +1,29 @@
+mod battery;
+mod csv;
+mod line;
+mod sine;
+
+pub use battery::BatterySource;
+pub use csv::{CsvError, CsvSource};
+pub use line::{LineError, LineSource, parse_line};
+pub use sine::SineSource;
+
+use crate::screen::Context;
+use std::time::Duration;
+
+/// A value taken at `time` seconds on the chart's clock.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sample {
+    pub time: f64,
+    pub value: f64,
+}
+
+/// Supplies timestamped samples to a [`ChartApp`](crate::chart::ChartApp) series.
+pub trait DataSource {
+    /// Appends the samples available at `now`, time since the chart started,
+    /// that weren't returned by a previous call.
+    fn pull(&mut self, now: Duration, ctx: &Context, samples: &mut Vec<Sample>);
+
+    /// Starts over from time zero, called whenever the chart becomes active.
+    fn reset(&mut self) {}
+}
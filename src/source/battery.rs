@@ -0,0 +1,65 @@
+use super::{DataSource, Sample};
+use crate::screen::Context;
+use std::time::Duration;
+
+/// Filtered battery voltage in volts, sampled at a fixed interval.
+#[derive(Debug, Clone)]
+pub struct BatterySource {
+    interval: Duration,
+    next: Duration,
+}
+
+impl BatterySource {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            next: Duration::ZERO,
+        }
+    }
+}
+
+impl DataSource for BatterySource {
+    fn pull(&mut self, now: Duration, ctx: &Context, samples: &mut Vec<Sample>) {
+        if now < self.next {
+            return;
+        }
+        self.next = now + self.interval;
+        if let Some(millivolts) = ctx.battery.millivolts() {
+            samples.push(Sample {
+                time: now.as_secs_f64(),
+                value: f64::from(millivolts) / 1000.0,
+            });
+        }
+    }
+
+    fn reset(&mut self) {
+        self.next = Duration::ZERO;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    #[test]
+    fn samples_the_filtered_voltage_at_interval() {
+        let mut ctx = Context::default();
+        let mut source = BatterySource::new(Duration::from_secs(1));
+        let mut samples = Vec::new();
+        source.pull(Duration::ZERO, &ctx, &mut samples);
+        assert!(samples.is_empty(), "no reading yet");
+
+        ctx.battery.push(3900, Instant::now());
+        source.pull(Duration::from_millis(500), &ctx, &mut samples);
+        source.pull(Duration::from_secs(1), &ctx, &mut samples);
+        source.pull(Duration::from_millis(1500), &ctx, &mut samples);
+        assert_eq!(
+            samples,
+            [Sample {
+                time: 1.0,
+                value: 3.9
+            }]
+        );
+    }
+}
@@ -0,0 +1,122 @@
+use super::{DataSource, Sample};
+use crate::screen::Context;
+use std::fmt;
+use std::time::Duration;
+
+/// Recorded samples replayed in real time from CSV with a header row.
+///
+/// The first column is the time in seconds, `column` names the value column.
+#[derive(Debug, Clone)]
+pub struct CsvSource {
+    samples: Vec<Sample>,
+    next: usize,
+}
+
+/// Why a recording couldn't be read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CsvError {
+    /// There is no header row.
+    Empty,
+    MissingColumn(String),
+    /// A field on the given 1-based line is not a number.
+    InvalidNumber(usize),
+}
+
+impl fmt::Display for CsvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CsvError::Empty => write!(f, "CSV has no header row"),
+            CsvError::MissingColumn(name) => write!(f, "CSV has no column {name:?}"),
+            CsvError::InvalidNumber(line) => write!(f, "CSV line {line} is not numeric"),
+        }
+    }
+}
+
+impl std::error::Error for CsvError {}
+
+impl CsvSource {
+    pub fn parse(text: &str, column: &str) -> Result<Self, CsvError> {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .filter(|(_, l)| !l.trim().is_empty());
+        let (_, header) = lines.next().ok_or(CsvError::Empty)?;
+        let index = header
+            .split(',')
+            .position(|name| name.trim() == column)
+            .ok_or_else(|| CsvError::MissingColumn(column.to_string()))?;
+
+        let mut samples = Vec::new();
+        for (number, line) in lines {
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            let field = |i: usize| fields.get(i).and_then(|field| field.parse::<f64>().ok());
+            let (Some(time), Some(value)) = (field(0), field(index)) else {
+                return Err(CsvError::InvalidNumber(number + 1));
+            };
+            samples.push(Sample { time, value });
+        }
+        samples.sort_by(|a, b| a.time.total_cmp(&b.time));
+        Ok(Self { samples, next: 0 })
+    }
+}
+
+impl DataSource for CsvSource {
+    fn pull(&mut self, now: Duration, _ctx: &Context, samples: &mut Vec<Sample>) {
+        let now = now.as_secs_f64();
+        let due = self.samples[self.next..]
+            .iter()
+            .take_while(|sample| sample.time <= now)
+            .count();
+        samples.extend_from_slice(&self.samples[self.next..self.next + due]);
+        self.next += due;
+    }
+
+    fn reset(&mut self) {
+        self.next = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RECORDING: &str = "time,volts,current\n\
+        0.0,4.02,0.11\n\
+        0.5,4.01,0.12\n\
+        \n\
+        1.0,3.99,0.18\n";
+
+    fn values(samples: &[Sample]) -> Vec<(f64, f64)> {
+        samples.iter().map(|s| (s.time, s.value)).collect()
+    }
+
+    #[test]
+    fn replays_selected_column() {
+        let ctx = Context::default();
+        let mut source = CsvSource::parse(RECORDING, "current").unwrap();
+        let mut samples = Vec::new();
+        source.pull(Duration::from_millis(600), &ctx, &mut samples);
+        assert_eq!(values(&samples), [(0.0, 0.11), (0.5, 0.12)]);
+        samples.clear();
+        source.pull(Duration::from_secs(5), &ctx, &mut samples);
+        assert_eq!(values(&samples), [(1.0, 0.18)]);
+
+        source.reset();
+        samples.clear();
+        source.pull(Duration::ZERO, &ctx, &mut samples);
+        assert_eq!(values(&samples), [(0.0, 0.11)]);
+    }
+
+    #[test]
+    fn reports_bad_input() {
+        assert_eq!(CsvSource::parse("", "volts").unwrap_err(), CsvError::Empty);
+        assert_eq!(
+            CsvSource::parse(RECORDING, "amps").unwrap_err(),
+            CsvError::MissingColumn("amps".to_string())
+        );
+        assert_eq!(
+            CsvSource::parse("time,volts\n0,4.0\n1,high\n", "volts").unwrap_err(),
+            CsvError::InvalidNumber(3)
+        );
+    }
+}
@@ -0,0 +1,105 @@
+use super::{DataSource, Sample};
+use crate::screen::Context;
+use std::fmt;
+use std::sync::mpsc::Receiver;
+use std::time::Duration;
+
+/// Values streamed as text lines, e.g. from a serial port, one number per line.
+///
+/// Each value is stamped with the time it was pulled. Empty lines and lines
+/// starting with `#` are ignored.
+#[derive(Debug)]
+pub struct LineSource {
+    lines: Receiver<String>,
+    rejected: usize,
+}
+
+impl LineSource {
+    pub fn new(lines: Receiver<String>) -> Self {
+        Self { lines, rejected: 0 }
+    }
+
+    /// Number of lines that weren't a number.
+    pub fn rejected(&self) -> usize {
+        self.rejected
+    }
+}
+
+/// Why a line of the protocol was rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LineError {
+    NotANumber(String),
+    /// NaN or infinity, which can't be plotted.
+    NotFinite,
+}
+
+impl fmt::Display for LineError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LineError::NotANumber(line) => write!(f, "`{line}` is not a number"),
+            LineError::NotFinite => write!(f, "value is not finite"),
+        }
+    }
+}
+
+impl std::error::Error for LineError {}
+
+/// Parses one line of the protocol, `Ok(None)` for lines without a value.
+pub fn parse_line(line: &str) -> Result<Option<f64>, LineError> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return Ok(None);
+    }
+    match line.parse::<f64>() {
+        Ok(value) if value.is_finite() => Ok(Some(value)),
+        Ok(_) => Err(LineError::NotFinite),
+        Err(_) => Err(LineError::NotANumber(line.to_string())),
+    }
+}
+
+impl DataSource for LineSource {
+    fn pull(&mut self, now: Duration, _ctx: &Context, samples: &mut Vec<Sample>) {
+        let time = now.as_secs_f64();
+        for line in self.lines.try_iter() {
+            match parse_line(&line) {
+                Ok(Some(value)) => samples.push(Sample { time, value }),
+                Ok(None) => {}
+                Err(_) => self.rejected += 1,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    #[test]
+    fn parses_values_and_skips_comments() {
+        assert_eq!(parse_line(" 3.25\r"), Ok(Some(3.25)));
+        assert_eq!(parse_line("-12"), Ok(Some(-12.0)));
+        assert_eq!(parse_line("# header"), Ok(None));
+        assert_eq!(parse_line(""), Ok(None));
+        assert_eq!(
+            parse_line("volts"),
+            Err(LineError::NotANumber("volts".to_string()))
+        );
+        assert_eq!(parse_line("NaN"), Err(LineError::NotFinite));
+    }
+
+    #[test]
+    fn stamps_lines_when_pulled() {
+        let (sender, receiver) = mpsc::channel();
+        let mut source = LineSource::new(receiver);
+        let ctx = Context::default();
+        for line in ["1.5", "oops", "# comment", "2.5"] {
+            sender.send(line.to_string()).unwrap();
+        }
+        let mut samples = Vec::new();
+        source.pull(Duration::from_secs(3), &ctx, &mut samples);
+        let values: Vec<(f64, f64)> = samples.iter().map(|s| (s.time, s.value)).collect();
+        assert_eq!(values, [(3.0, 1.5), (3.0, 2.5)]);
+        assert_eq!(source.rejected(), 1);
+    }
+}
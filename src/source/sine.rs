@@ -0,0 +1,69 @@
+use super::{DataSource, Sample};
+use crate::screen::Context;
+use std::time::Duration;
+
+/// Synthetic sine wave sampled at a fixed interval.
+#[derive(Debug, Clone)]
+pub struct SineSource {
+    /// Seconds between samples.
+    interval: f64,
+    period: f64,
+    scale: f64,
+    next: f64,
+}
+
+impl SineSource {
+    pub const fn new(interval: f64, period: f64, scale: f64) -> Self {
+        assert!(interval > 0.0, "SineSource needs a positive interval");
+        Self {
+            interval,
+            period,
+            scale,
+            next: 0.0,
+        }
+    }
+}
+
+impl DataSource for SineSource {
+    fn pull(&mut self, now: Duration, _ctx: &Context, samples: &mut Vec<Sample>) {
+        let now = now.as_secs_f64();
+        while self.next <= now {
+            samples.push(Sample {
+                time: self.next,
+                value: (self.next / self.period).sin() * self.scale,
+            });
+            self.next += self.interval;
+        }
+    }
+
+    fn reset(&mut self) {
+        self.next = 0.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn samples_at_interval_from_zero() {
+        let ctx = Context::default();
+        let mut source = SineSource::new(0.5, 1.0, 2.0);
+        let mut samples = Vec::new();
+        source.pull(Duration::from_millis(1200), &ctx, &mut samples);
+        let times: Vec<f64> = samples.iter().map(|sample| sample.time).collect();
+        assert_eq!(times, [0.0, 0.5, 1.0]);
+        assert_eq!(samples[2].value, 1.0_f64.sin() * 2.0);
+
+        source.reset();
+        samples.clear();
+        source.pull(Duration::ZERO, &ctx, &mut samples);
+        assert_eq!(samples.len(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "positive interval")]
+    fn rejects_a_zero_interval() {
+        SineSource::new(0.0, 1.0, 1.0);
+    }
+}
@@ -0,0 +1,159 @@
+use crate::battery::{BatteryState, ChargeState};
+use crate::screen::Context;
+use crate::theme::Theme;
+use mousefood::prelude::*;
+use time::OffsetDateTime;
+
+/// Battery icons from empty to full.
+const LEVELS: [&str; 8] = ["▁", "▂", "▃", "▄", "▅", "▆", "▇", "█"];
+/// Charge at or below which the battery icon is drawn in the danger colour.
+const LOW_PERCENT: u8 = 15;
+
+/// Splits `area` into the area of the screen and the status bar line below it.
+pub fn layout(area: Rect) -> [Rect; 2] {
+    Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(area)
+}
+
+/// The line below every screen with button hints on the left and battery, time
+/// and screen position on the right.
+///
+/// When the hints need the room, the time is left out first, then the battery.
+#[derive(Debug, Clone)]
+pub struct StatusBar<'a> {
+    hints: &'a str,
+    battery: Option<BatteryState>,
+    time: Option<OffsetDateTime>,
+    /// Number of the screen from 1 and the number of screens.
+    position: Option<(usize, usize)>,
+    theme: Theme,
+}
+
+impl<'a> StatusBar<'a> {
+    /// A bar with `hints` and the battery, time and theme of `ctx`.
+    pub fn new(hints: &'a str, ctx: &Context) -> Self {
+        Self {
+            hints,
+            battery: ctx.battery.state(),
+            time: ctx.clock.now(),
+            position: None,
+            theme: ctx.theme,
+        }
+    }
+
+    /// Shows the screen at `index` out of `count`, e.g. `3/5`.
+    pub fn position(mut self, index: usize, count: usize) -> Self {
+        self.position = Some((index + 1, count));
+        self
+    }
+
+    fn battery_spans(&self, state: BatteryState) -> Vec<Span<'static>> {
+        let theme = &self.theme;
+        let level = usize::from(state.percent.min(100)) * (LEVELS.len() - 1) / 100;
+        let color = match state.charge {
+            ChargeState::Charging => theme.good,
+            _ if state.percent <= LOW_PERCENT => theme.danger,
+            _ => theme.text,
+        };
+        vec![
+            Span::styled(LEVELS[level], Style::new().fg(color).bg(theme.surface)),
+            Span::raw(format!("{}%", state.percent)).fg(theme.text),
+        ]
+    }
+}
+
+impl Widget for StatusBar<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let theme = &self.theme;
+        let battery = self.battery.map(|state| self.battery_spans(state));
+        let time = self.time.map(|time| {
+            let time = format!("{:02}:{:02}", time.hour(), time.minute());
+            vec![Span::raw(time).fg(theme.text)]
+        });
+        let position = self
+            .position
+            .map(|(number, count)| vec![Span::raw(format!("{number}/{count}")).fg(theme.footer)]);
+
+        let mut items = [battery, time, position];
+        let hints = Line::raw(self.hints).fg(theme.footer);
+        for dropped in [1, 0] {
+            if hints.width() + status_width(&items) <= usize::from(area.width) {
+                break;
+            }
+            items[dropped] = None;
+        }
+
+        let mut status = Vec::new();
+        for item in items.into_iter().flatten() {
+            status.push(Span::raw(" "));
+            status.extend(item);
+        }
+        let status = Line::from(status);
+        let [hints_area, status_area] = Layout::horizontal([
+            Constraint::Min(0),
+            Constraint::Length(status.width() as u16),
+        ])
+        .areas(area);
+        hints.render(hints_area, buf);
+        status.render(status_area, buf);
+    }
+}
+
+/// Width of the status items, each with a space in front.
+fn status_width(items: &[Option<Vec<Span>>]) -> usize {
+    items
+        .iter()
+        .flatten()
+        .map(|item| 1 + item.iter().map(Span::width).sum::<usize>())
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::{Date, Month, PrimitiveDateTime, Time};
+
+    fn bar(hints: &str) -> StatusBar<'_> {
+        let time = PrimitiveDateTime::new(
+            Date::from_calendar_date(2025, Month::May, 23).unwrap(),
+            Time::from_hms(9, 5, 0).unwrap(),
+        );
+        StatusBar {
+            hints,
+            battery: Some(BatteryState {
+                millivolts: 3900,
+                percent: 72,
+                charge: ChargeState::Discharging,
+            }),
+            time: Some(time.assume_utc()),
+            position: None,
+            theme: Theme::default(),
+        }
+        .position(2, 8)
+    }
+
+    fn text(bar: StatusBar) -> String {
+        let mut buf = Buffer::empty(Rect::new(0, 0, 40, 1));
+        bar.render(buf.area, &mut buf);
+        buf.content.iter().map(|cell| cell.symbol()).collect()
+    }
+
+    #[test]
+    fn shows_hints_battery_time_and_position() {
+        assert_eq!(
+            text(bar("[S1] next  [2x] back")),
+            "[S1] next  [2x] back      ▆72% 09:05 3/8"
+        );
+    }
+
+    #[test]
+    fn leaves_out_the_time_for_long_hints() {
+        assert_eq!(
+            text(bar("[S1] next  [hold] calibrate")),
+            "[S1] next  [hold] calibrate     ▆72% 3/8"
+        );
+        assert_eq!(
+            text(bar("[S1] next  [2x] back  [hold] something")),
+            "[S1] next  [2x] back  [hold] somethi 3/8"
+        );
+    }
+}
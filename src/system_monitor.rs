@@ -0,0 +1,181 @@
+use crate::metrics::{MemoryStats, MetricsSource, SystemMetrics, format_bytes, format_uptime};
+use crate::screen::{Context, Screen};
+use crate::theme::Theme;
+use mousefood::prelude::*;
+use mousefood::ratatui::widgets::{Block, Cell, Gauge, Row, Table};
+use std::time::Duration;
+
+/// How often the metrics are sampled, which is also the CPU load window.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Free stack below which a task is shown in red, or yellow under twice as much.
+const LOW_STACK_BYTES: usize = 512;
+
+/// Shows heap, uptime, reset reason, tasks and CPU load of the board.
+pub struct SystemMonitorApp {
+    source: Box<dyn MetricsSource>,
+    metrics: SystemMetrics,
+    since_refresh: Duration,
+}
+
+impl SystemMonitorApp {
+    pub fn new(source: Box<dyn MetricsSource>) -> Self {
+        Self {
+            source,
+            metrics: SystemMetrics::default(),
+            since_refresh: Duration::ZERO,
+        }
+    }
+
+    /// Takes a new sample now.
+    pub fn refresh(&mut self) {
+        self.metrics = self.source.sample();
+        self.since_refresh = Duration::ZERO;
+    }
+}
+
+impl Screen for SystemMonitorApp {
+    fn name(&self) -> &'static str {
+        "system"
+    }
+
+    fn init(&mut self, _ctx: &mut Context) {
+        self.source.reset();
+        self.refresh();
+    }
+
+    fn tick(&mut self, dt: Duration, _ctx: &mut Context) {
+        self.since_refresh += dt;
+        if self.since_refresh >= REFRESH_INTERVAL {
+            self.refresh();
+        }
+    }
+
+    fn draw(&self, frame: &mut Frame, area: Rect, ctx: &Context) {
+        let theme = &ctx.theme;
+        let metrics = &self.metrics;
+        let psram_height = if metrics.psram.is_some() { 1 } else { 0 };
+        let [
+            header_area,
+            heap_area,
+            heap_details_area,
+            psram_area,
+            cpu_area,
+            tasks_area,
+        ] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Length(psram_height),
+            Constraint::Length(1),
+            Constraint::Min(3),
+        ])
+        .areas(area);
+
+        let header = Line::from(vec![
+            "up ".fg(theme.footer),
+            format_uptime(metrics.uptime).into(),
+            "  reset ".fg(theme.footer),
+            metrics.reset_reason.to_string().into(),
+        ]);
+        frame.render_widget(header.centered(), header_area);
+
+        let heap = memory_gauge("heap", &metrics.heap, theme);
+        frame.render_widget(heap, heap_area);
+        let details = Line::from(vec![
+            "min ".fg(theme.footer),
+            format_bytes(metrics.heap.min_free).into(),
+            "  largest block ".fg(theme.footer),
+            format_bytes(metrics.heap.largest_block).into(),
+        ]);
+        frame.render_widget(details.centered(), heap_details_area);
+        if let Some(psram) = &metrics.psram {
+            frame.render_widget(memory_gauge("psram", psram, theme), psram_area);
+        }
+
+        self.draw_cpu_load(frame, cpu_area, theme);
+        self.draw_tasks(frame, tasks_area, theme);
+    }
+}
+
+impl SystemMonitorApp {
+    fn draw_cpu_load(&self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        let loads = &self.metrics.cpu_load;
+        if loads.is_empty() {
+            let placeholder = Line::raw("cpu --").centered().fg(theme.footer);
+            frame.render_widget(placeholder, area);
+            return;
+        }
+        let areas = Layout::horizontal(vec![Constraint::Fill(1); loads.len()])
+            .spacing(1)
+            .split(area);
+        for (core, (&load, &area)) in loads.iter().zip(areas.iter()).enumerate() {
+            let color = match load {
+                0..60 => theme.good,
+                60..85 => theme.warning,
+                _ => theme.danger,
+            };
+            let gauge = Gauge::default()
+                .gauge_style(color)
+                .percent(u16::from(load.min(100)))
+                .label(format!("cpu{core} {load}%"))
+                .use_unicode(true);
+            frame.render_widget(gauge, area);
+        }
+    }
+
+    fn draw_tasks(&self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        // Tasks closest to overflowing their stack first.
+        let mut tasks: Vec<_> = self.metrics.tasks.iter().collect();
+        tasks.sort_by_key(|task| task.stack_high_water);
+        let rows = tasks.into_iter().map(|task| {
+            let stack_color = if task.stack_high_water < LOW_STACK_BYTES {
+                theme.danger
+            } else if task.stack_high_water < LOW_STACK_BYTES * 2 {
+                theme.warning
+            } else {
+                theme.text
+            };
+            let core = match task.core {
+                Some(core) => core.to_string(),
+                None => "-".to_string(),
+            };
+            Row::new(vec![
+                Cell::from(task.name.as_str()),
+                Cell::from(format_bytes(task.stack_high_water)).style(stack_color),
+                Cell::from(core),
+            ])
+        });
+        let header_style = Style::new().fg(theme.footer).bold();
+        let header = Row::new(["task", "free stack", "core"]).style(header_style);
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Min(8),
+                Constraint::Length(10),
+                Constraint::Length(4),
+            ],
+        )
+        .header(header)
+        .block(
+            Block::bordered()
+                .border_style(theme.border)
+                .title(format!("Tasks ({})", self.metrics.tasks.len())),
+        );
+        frame.render_widget(table, area);
+    }
+}
+
+/// One-line gauge of the memory in use, labelled with the free size.
+fn memory_gauge(name: &str, memory: &MemoryStats, theme: &Theme) -> Gauge<'static> {
+    let label = format!(
+        "{name} {} free of {}",
+        format_bytes(memory.free),
+        format_bytes(memory.total)
+    );
+    Gauge::default()
+        .gauge_style(theme.secondary)
+        .ratio(memory.used_ratio())
+        .label(label)
+        .use_unicode(true)
+}
@@ -1,114 +1,179 @@
+use crate::clock::add_months;
+use crate::input::InputEvent;
 use crate::lorem::LOREM_IPSUM;
-use esp_idf_svc::hal::delay;
-use esp_idf_svc::hal::gpio::{Gpio0, Input, PinDriver};
-use esp_idf_svc::hal::task::notification::Notification;
+use crate::screen::{Action, Context, Screen};
+use crate::theme::Theme;
 use mousefood::prelude::*;
 use mousefood::ratatui::style::Style;
 use mousefood::ratatui::widgets::calendar::{CalendarEventStore, Monthly};
 use mousefood::ratatui::widgets::{Bar, BarChart, BarGroup, Block, Padding, Paragraph, Tabs, Wrap};
 use rand::{Rng, rng};
-use std::marker::PhantomData;
-use time::{Date, Month};
+use std::time::Duration;
+use time::Date;
 
-pub struct TabsApp<B: Backend> {
+const TAB_COUNT: usize = 3;
+const CALENDAR_TAB: usize = 1;
+
+pub struct TabsApp {
     selected_tab: usize,
     temperatures: Vec<u8>,
-    _marker: PhantomData<B>,
+    /// Local date from the [`Clock`](crate::clock::Clock), `None` until it is set.
+    today: Option<Date>,
+    /// Months between the one shown by the calendar and the current one.
+    month_offset: i32,
+    /// Whether the button moves between months instead of tabs.
+    browsing: bool,
 }
 
-impl<B: Backend> TabsApp<B> {
+impl TabsApp {
     pub fn new() -> Self {
         let mut rng = rng();
         let temperatures = (0..6).map(|_| rng.random_range(50..90)).collect();
+        Self::with_temperatures(temperatures)
+    }
+
+    /// Creates the app with fixed bar chart values instead of random ones.
+    pub fn with_temperatures(temperatures: Vec<u8>) -> Self {
         Self {
             selected_tab: 0,
             temperatures,
-            _marker: PhantomData,
+            today: None,
+            month_offset: 0,
+            browsing: false,
         }
     }
-    pub fn run(
-        mut self,
-        terminal: &mut Terminal<B>,
-        notification: &mut Notification,
-        button: &mut PinDriver<Gpio0, Input>,
-    ) -> std::io::Result<()> {
-        button.enable_interrupt().unwrap();
-        loop {
-            if notification.wait(delay::NON_BLOCK).is_some() {
-                if self.selected_tab == 2 {
-                    return Ok(());
+
+    fn update_today(&mut self, ctx: &Context) {
+        self.today = ctx.clock.now().map(|now| now.date());
+    }
+}
+
+impl Screen for TabsApp {
+    fn name(&self) -> &'static str {
+        "tabs"
+    }
+
+    fn init(&mut self, ctx: &mut Context) {
+        self.selected_tab = 0;
+        self.month_offset = 0;
+        self.browsing = false;
+        self.update_today(ctx);
+    }
+
+    fn handle_event(&mut self, event: InputEvent, ctx: &mut Context) -> Action {
+        self.update_today(ctx);
+        if self.browsing {
+            match event {
+                InputEvent::ShortPress | InputEvent::Repeat => self.month_offset += 1,
+                InputEvent::DoublePress => self.month_offset -= 1,
+                InputEvent::LongPress => {
+                    self.browsing = false;
+                    self.month_offset = 0;
                 }
-                self.selected_tab += 1;
-                button.enable_interrupt().unwrap();
             }
-            terminal.draw(|frame| frame.render_widget(&self, frame.area()))?;
+            return Action::Stay;
+        }
+        match event {
+            InputEvent::ShortPress | InputEvent::Repeat => {
+                self.selected_tab = (self.selected_tab + 1) % TAB_COUNT;
+                Action::Stay
+            }
+            InputEvent::DoublePress
+                if self.selected_tab == CALENDAR_TAB && self.today.is_some() =>
+            {
+                self.browsing = true;
+                Action::Stay
+            }
+            InputEvent::LongPress => Action::NextScreen,
+            InputEvent::DoublePress => Action::PreviousScreen,
         }
     }
+
+    fn tick(&mut self, _dt: Duration, ctx: &mut Context) {
+        self.update_today(ctx);
+    }
+
+    fn hints(&self) -> &'static str {
+        if self.browsing {
+            "[S1/2x] month [hold] done"
+        } else if self.selected_tab == CALENDAR_TAB && self.today.is_some() {
+            "[S1] tab  [2x] months"
+        } else {
+            "[S1] tab  [hold] next"
+        }
+    }
+
+    fn draw(&self, frame: &mut Frame, area: Rect, ctx: &Context) {
+        self.render(area, frame.buffer_mut(), &ctx.theme);
+    }
 }
 
-impl<B: Backend> Widget for &TabsApp<B> {
-    fn render(self, area: Rect, buf: &mut Buffer) {
+impl TabsApp {
+    fn render(&self, area: Rect, buf: &mut Buffer, theme: &Theme) {
         use Constraint::{Length, Min};
-        let vertical = Layout::vertical([Length(1), Min(0), Length(1)]);
-        let [header_area, inner_area, footer_area] = vertical.areas(area);
+        let vertical = Layout::vertical([Length(1), Min(0)]);
+        let [header_area, inner_area] = vertical.areas(area);
 
-        self.render_tabs(header_area, buf);
+        self.render_tabs(header_area, buf, theme);
         let block = Block::bordered()
             .border_set(symbols::border::PROPORTIONAL_TALL)
             .padding(Padding::horizontal(1))
-            .border_style(Color::Yellow);
+            .border_style(theme.border);
         match self.selected_tab {
             0 => {
                 Paragraph::new(LOREM_IPSUM)
                     .wrap(Wrap { trim: true })
                     .block(block)
                     .render(inner_area, buf);
-                render_footer("[S1] to change tab", footer_area, buf);
-            }
-            1 => {
-                let default_style = Style::default()
-                    .bg(Color::Rgb(50, 50, 50))
-                    .fg(Color::Yellow);
-                let list = CalendarEventStore::today(Style::default().bg(Color::Blue));
-                Monthly::new(
-                    Date::from_calendar_date(2025, Month::May, 23).unwrap(),
-                    list,
-                )
-                .show_month_header(Style::default().fg(Color::Yellow))
-                .default_style(default_style)
-                .block(block)
-                .render(inner_area, buf);
-                render_footer("[S1] to change tab", footer_area, buf);
             }
+            CALENDAR_TAB => match self.today {
+                Some(today) => {
+                    let default_style = Style::default().bg(theme.surface).fg(theme.accent);
+                    let mut events = CalendarEventStore::default();
+                    events.add(today, theme.highlighted());
+                    let month = match self.month_offset {
+                        0 => today,
+                        offset => add_months(today, offset),
+                    };
+                    Monthly::new(month, events)
+                        .show_month_header(Style::default().fg(theme.accent))
+                        .default_style(default_style)
+                        .block(block)
+                        .render(inner_area, buf);
+                }
+                None => {
+                    Paragraph::new("Clock not set".fg(theme.footer))
+                        .centered()
+                        .block(block)
+                        .render(inner_area, buf);
+                }
+            },
             2 => {
                 vertical_barchart(&self.temperatures)
                     .block(block)
                     .render(inner_area, buf);
-                render_footer("[S1] to change screen", footer_area, buf);
             }
             _ => {}
         }
     }
-}
 
-impl<B: Backend> TabsApp<B> {
-    fn render_tabs(&self, area: Rect, buf: &mut Buffer) {
+    fn render_tabs(&self, area: Rect, buf: &mut Buffer, theme: &Theme) {
         let titles = ["[Paragraph]", "[Calendar]", "[Barchart]"];
         Tabs::new(titles)
-            .style(Style::new().bg(Color::Black).fg(Color::Yellow))
-            .highlight_style(Style::new().bg(Color::Yellow).fg(Color::Black))
+            .style(Style::new().bg(theme.background).fg(theme.accent))
+            .highlight_style(theme.highlighted())
             .select(self.selected_tab)
             .render(area, buf);
     }
 }
 
-impl<B: Backend> Default for TabsApp<B> {
+impl Default for TabsApp {
     fn default() -> Self {
         Self::new()
     }
 }
 
-fn vertical_barchart(temperatures: &[u8]) -> BarChart {
+fn vertical_barchart(temperatures: &[u8]) -> BarChart<'_> {
     let bars: Vec<Bar> = temperatures
         .iter()
         .enumerate()
@@ -121,7 +186,7 @@ fn vertical_barchart(temperatures: &[u8]) -> BarChart {
         .bar_width(5)
 }
 
-fn vertical_bar(hour: usize, temperature: &u8) -> Bar {
+fn vertical_bar(hour: usize, temperature: &u8) -> Bar<'_> {
     Bar::default()
         .value(u64::from(*temperature))
         .label(Line::from(format!("{hour:>02}:00")))
@@ -141,7 +206,3 @@ fn label_style(value: u8) -> Style {
     let color = Color::Rgb(255, green, 0);
     Style::new().bg(color).fg(Color::Black)
 }
-
-fn render_footer(content: &str, area: Rect, buf: &mut Buffer) {
-    Line::raw(content).centered().gray().render(area, buf);
-}
@@ -0,0 +1,155 @@
+use mousefood::prelude::*;
+
+/// Colours for every role the screens draw with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    pub name: &'static str,
+    /// Fills the screen before anything is drawn.
+    pub background: Color,
+    pub text: Color,
+    /// Footers, axis labels and other secondary text.
+    pub footer: Color,
+    pub border: Color,
+    /// Titles, headers and the main data series.
+    pub accent: Color,
+    /// Gauges and the data series next to the accent one.
+    pub secondary: Color,
+    /// Behind the selected item, which is drawn in the background colour.
+    pub highlight: Color,
+    /// Behind raised areas such as the calendar.
+    pub surface: Color,
+    /// Values in their normal range, such as a charging battery.
+    pub good: Color,
+    /// Values getting close to a limit.
+    pub warning: Color,
+    /// Values past a limit and failures.
+    pub danger: Color,
+}
+
+impl Theme {
+    /// Text on the background.
+    pub fn base(&self) -> Style {
+        Style::new().fg(self.text).bg(self.background)
+    }
+
+    /// The selected item, e.g. the active tab.
+    pub fn highlighted(&self) -> Style {
+        Style::new().fg(self.background).bg(self.highlight)
+    }
+
+    /// Colour of the `index`th data series on a chart.
+    pub fn series(&self, index: usize) -> Color {
+        [self.secondary, self.accent][index % 2]
+    }
+
+    pub fn by_name(name: &str) -> Option<&'static Theme> {
+        THEMES.iter().find(|theme| theme.name == name)
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        DARK
+    }
+}
+
+/// Yellow and cyan on black, the colours the demo started with.
+pub const DARK: Theme = Theme {
+    name: "dark",
+    background: Color::Black,
+    text: Color::White,
+    footer: Color::Gray,
+    border: Color::Yellow,
+    accent: Color::Yellow,
+    secondary: Color::Cyan,
+    highlight: Color::Yellow,
+    surface: Color::Rgb(50, 50, 50),
+    good: Color::Green,
+    warning: Color::Yellow,
+    danger: Color::Red,
+};
+
+/// Dark text on white, for bright rooms.
+pub const LIGHT: Theme = Theme {
+    name: "light",
+    background: Color::White,
+    text: Color::Black,
+    footer: Color::Rgb(90, 90, 90),
+    border: Color::Rgb(0, 90, 180),
+    accent: Color::Rgb(0, 90, 180),
+    secondary: Color::Rgb(170, 40, 140),
+    highlight: Color::Rgb(0, 90, 180),
+    surface: Color::Rgb(225, 225, 225),
+    good: Color::Rgb(0, 130, 0),
+    warning: Color::Rgb(200, 110, 0),
+    danger: Color::Rgb(220, 30, 30),
+};
+
+/// Only fully saturated colours on black, readable on a dim or washed out panel.
+pub const HIGH_CONTRAST: Theme = Theme {
+    name: "high-contrast",
+    background: Color::Black,
+    text: Color::White,
+    footer: Color::White,
+    border: Color::White,
+    accent: Color::Rgb(255, 255, 0),
+    secondary: Color::Rgb(0, 255, 255),
+    highlight: Color::White,
+    surface: Color::Black,
+    good: Color::Rgb(0, 255, 0),
+    warning: Color::Rgb(255, 255, 0),
+    danger: Color::Rgb(255, 60, 60),
+};
+
+/// The Okabe-Ito palette, which stays distinguishable with every common colour
+/// vision deficiency.
+pub const COLOR_BLIND: Theme = Theme {
+    name: "colorblind",
+    background: Color::Black,
+    text: Color::White,
+    footer: Color::Gray,
+    border: Color::Rgb(86, 180, 233),
+    accent: Color::Rgb(230, 159, 0),
+    secondary: Color::Rgb(86, 180, 233),
+    highlight: Color::Rgb(230, 159, 0),
+    surface: Color::Rgb(50, 50, 50),
+    good: Color::Rgb(0, 158, 115),
+    warning: Color::Rgb(240, 228, 66),
+    danger: Color::Rgb(213, 94, 0),
+};
+
+pub const THEMES: &[Theme] = &[DARK, LIGHT, HIGH_CONTRAST, COLOR_BLIND];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn theme_names_are_unique() {
+        for theme in THEMES {
+            assert_eq!(Theme::by_name(theme.name), Some(theme));
+        }
+    }
+
+    #[test]
+    fn roles_stand_out_from_the_background() {
+        for theme in THEMES {
+            let roles = [
+                theme.text,
+                theme.footer,
+                theme.border,
+                theme.accent,
+                theme.secondary,
+                theme.highlight,
+                theme.good,
+                theme.warning,
+                theme.danger,
+            ];
+            assert!(
+                !roles.contains(&theme.background),
+                "{} draws a role in its background colour",
+                theme.name
+            );
+        }
+    }
+}
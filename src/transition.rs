@@ -0,0 +1,250 @@
+use mousefood::ratatui::buffer::Buffer;
+use std::fmt;
+use std::time::Duration;
+
+/// How the incoming screen replaces the outgoing one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Effect {
+    /// Shows the incoming screen right away.
+    Cut,
+    /// The incoming screen pushes the outgoing one out to the left.
+    SlideLeft,
+    /// The incoming screen pushes the outgoing one out to the right.
+    SlideRight,
+    /// The incoming screen is uncovered from left to right.
+    Wipe,
+    /// Cells switch over one by one in a fixed scattered order.
+    Dissolve,
+}
+
+impl Effect {
+    pub const ALL: [Effect; 5] = [
+        Self::Cut,
+        Self::SlideLeft,
+        Self::SlideRight,
+        Self::Wipe,
+        Self::Dissolve,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Effect::Cut => "cut",
+            Effect::SlideLeft => "slide-left",
+            Effect::SlideRight => "slide-right",
+            Effect::Wipe => "wipe",
+            Effect::Dissolve => "dissolve",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|effect| effect.name() == name)
+    }
+}
+
+/// An effect played over `duration` when switching screens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Transition {
+    pub effect: Effect,
+    pub duration: Duration,
+}
+
+impl Transition {
+    pub const CUT: Transition = Transition::new(Effect::Cut, Duration::ZERO);
+
+    pub const fn new(effect: Effect, duration: Duration) -> Self {
+        Self { effect, duration }
+    }
+
+    /// Eased progress `elapsed` into the transition, `None` once it is over.
+    pub fn progress(&self, elapsed: Duration) -> Option<f32> {
+        if self.effect == Effect::Cut || elapsed >= self.duration {
+            return None;
+        }
+        let linear = elapsed.as_secs_f32() / self.duration.as_secs_f32();
+        // Ease out, so the incoming screen settles gently.
+        Some(1.0 - (1.0 - linear).powi(2))
+    }
+}
+
+impl fmt::Display for Transition {
+    /// Formats the transition the way [`parse_transition`] reads it.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.effect {
+            Effect::Cut => f.write_str(self.effect.name()),
+            effect => write!(f, "{}:{}", effect.name(), self.duration.as_millis()),
+        }
+    }
+}
+
+/// How long an effect takes unless given a duration.
+pub const DEFAULT_DURATION: Duration = Duration::from_millis(300);
+
+/// Parses an effect with an optional duration in milliseconds, e.g. `slide-left:300`.
+/// Without a duration an effect takes [`DEFAULT_DURATION`].
+pub fn parse_transition(text: &str) -> Option<Transition> {
+    let (effect, duration) = match text.split_once(':') {
+        Some((effect, millis)) => (effect, Duration::from_millis(millis.parse().ok()?)),
+        None => (text, DEFAULT_DURATION),
+    };
+    Some(Transition::new(Effect::from_name(effect)?, duration))
+}
+
+/// Which transition plays for each way of switching screens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransitionConfig {
+    /// Moving on with S1 or the carousel.
+    pub next: Transition,
+    /// Going back with a double press.
+    pub previous: Transition,
+    /// Jumping to a screen from the console.
+    pub jump: Transition,
+}
+
+impl Default for TransitionConfig {
+    fn default() -> Self {
+        Self {
+            next: Transition::new(Effect::SlideLeft, DEFAULT_DURATION),
+            previous: Transition::new(Effect::SlideRight, DEFAULT_DURATION),
+            jump: Transition::new(Effect::Dissolve, Duration::from_millis(400)),
+        }
+    }
+}
+
+/// Blends the outgoing frame `from` into the incoming frame `to` at `progress`
+/// from 0 (all `from`) to 1 (all `to`).
+///
+/// Frames of different sizes can't be blended, so `to` is left as it is.
+pub fn composite(effect: Effect, from: &Buffer, to: &mut Buffer, progress: f32) {
+    if from.area != to.area {
+        return;
+    }
+    let area = to.area;
+    let progress = progress.clamp(0.0, 1.0);
+    let shift = (progress * f32::from(area.width)).round() as u16;
+    match effect {
+        Effect::Cut => {}
+        Effect::SlideLeft | Effect::SlideRight => {
+            let incoming = to.clone();
+            let (width, shift) = (i32::from(area.width), i32::from(shift));
+            // Where the first column of each frame is drawn.
+            let (outgoing_start, incoming_start) = match effect {
+                Effect::SlideLeft => (-shift, width - shift),
+                _ => (shift, shift - width),
+            };
+            for y in area.top()..area.bottom() {
+                for column in 0..width {
+                    let (buffer, source) = if (0..width).contains(&(column - outgoing_start)) {
+                        (from, column - outgoing_start)
+                    } else {
+                        (&incoming, column - incoming_start)
+                    };
+                    let x = area.x + column as u16;
+                    to[(x, y)] = buffer[(area.x + source as u16, y)].clone();
+                }
+            }
+        }
+        Effect::Wipe => {
+            for y in area.top()..area.bottom() {
+                for x in area.left() + shift..area.right() {
+                    to[(x, y)] = from[(x, y)].clone();
+                }
+            }
+        }
+        Effect::Dissolve => {
+            for y in area.top()..area.bottom() {
+                for x in area.left()..area.right() {
+                    if dissolve_rank(x - area.x, y - area.y) >= progress {
+                        to[(x, y)] = from[(x, y)].clone();
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// When a cell switches over in a dissolve, scattered evenly over 0 to 1.
+fn dissolve_rank(x: u16, y: u16) -> f32 {
+    let mut hash = u32::from(x).wrapping_mul(0x9e37_79b1) ^ u32::from(y).wrapping_mul(0x85eb_ca77);
+    hash ^= hash >> 15;
+    hash = hash.wrapping_mul(0x2c1b_3c6d);
+    hash ^= hash >> 12;
+    (hash >> 8) as f32 / (1 << 24) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mousefood::prelude::*;
+    use mousefood::ratatui::buffer::Cell;
+
+    fn frames() -> (Buffer, Buffer) {
+        let area = Rect::new(0, 0, 4, 2);
+        let from = Buffer::with_lines(["abcd", "efgh"]);
+        let to = Buffer::with_lines(["ABCD", "EFGH"]);
+        assert_eq!(from.area, area);
+        (from, to)
+    }
+
+    fn blend(effect: Effect, progress: f32) -> Buffer {
+        let (from, mut to) = frames();
+        composite(effect, &from, &mut to, progress);
+        to
+    }
+
+    #[test]
+    fn slides_and_wipes() {
+        assert_eq!(
+            blend(Effect::SlideLeft, 0.5),
+            Buffer::with_lines(["cdAB", "ghEF"])
+        );
+        assert_eq!(
+            blend(Effect::SlideRight, 0.25),
+            Buffer::with_lines(["Dabc", "Hefg"])
+        );
+        assert_eq!(
+            blend(Effect::Wipe, 0.75),
+            Buffer::with_lines(["ABCd", "EFGh"])
+        );
+        for effect in Effect::ALL {
+            assert_eq!(blend(effect, 1.0), frames().1, "{effect:?}");
+            if effect != Effect::Cut {
+                assert_eq!(blend(effect, 0.0), frames().0, "{effect:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn dissolves_cells_in_a_fixed_order() {
+        let area = Rect::new(0, 0, 40, 13);
+        let from = Buffer::filled(area, Cell::new("a"));
+        let mut to = Buffer::filled(area, Cell::new("B"));
+        composite(Effect::Dissolve, &from, &mut to, 0.5);
+        let switched = to
+            .content
+            .iter()
+            .filter(|cell| cell.symbol() == "B")
+            .count();
+        assert!((200..320).contains(&switched), "{switched} of 520 cells");
+        let mut again = Buffer::filled(area, Cell::new("B"));
+        composite(Effect::Dissolve, &from, &mut again, 0.5);
+        assert_eq!(again, to);
+    }
+
+    #[test]
+    fn parses_transitions() {
+        assert_eq!(
+            parse_transition("wipe:150"),
+            Some(Transition::new(Effect::Wipe, Duration::from_millis(150)))
+        );
+        assert_eq!(
+            parse_transition("dissolve"),
+            Some(Transition::new(Effect::Dissolve, DEFAULT_DURATION))
+        );
+        assert_eq!(parse_transition("fade"), None);
+        assert_eq!(Transition::CUT.to_string(), "cut");
+        let slide = Transition::new(Effect::SlideLeft, DEFAULT_DURATION);
+        assert_eq!(parse_transition(&slide.to_string()), Some(slide));
+        assert_eq!(slide.progress(Duration::ZERO), Some(0.0));
+        assert_eq!(slide.progress(DEFAULT_DURATION), None);
+    }
+}
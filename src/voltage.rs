@@ -1,65 +1,158 @@
-use esp_idf_svc::hal::adc::ADC1;
-use esp_idf_svc::hal::adc::oneshot::{AdcChannelDriver, AdcDriver};
-use esp_idf_svc::hal::delay;
-use esp_idf_svc::hal::gpio::{Gpio0, Gpio34, Input, PinDriver};
-use esp_idf_svc::hal::task::notification::Notification;
+use crate::battery::ChargeState;
+use crate::helpers::center;
+use crate::input::InputEvent;
+use crate::screen::{Action, Context, Screen};
 use mousefood::prelude::*;
-use mousefood::ratatui::widgets::{Block, BorderType, Padding};
-use std::io::Result;
-use std::marker::PhantomData;
+use mousefood::ratatui::layout::Flex;
+use mousefood::ratatui::widgets::{Block, BorderType};
 use tui_big_text::{BigText, PixelSize};
 
-pub struct VoltageApp<B: Backend> {
-    _marker: PhantomData<B>,
+/// Step of the reference voltage while calibrating.
+const REFERENCE_STEP_MILLIVOLTS: u16 = 10;
+
+/// Shows the battery state. Holding S1 starts calibrating the reading against a
+/// reference voltage measured with a multimeter.
+#[derive(Debug, Default)]
+pub struct VoltageApp {
+    /// Reference voltage being entered, `None` outside calibration.
+    reference: Option<u16>,
+    /// Whether the last calibration was rejected as implausible.
+    rejected: bool,
 }
 
-impl<B: Backend> VoltageApp<B> {
+impl VoltageApp {
     pub fn new() -> Self {
-        Self {
-            _marker: PhantomData,
+        Self::default()
+    }
+
+    fn calibrate(&mut self, reference: u16, ctx: &mut Context) {
+        let calibration = ctx
+            .battery
+            .millivolts()
+            .and_then(|measured| ctx.calibration.with_reference(measured, reference));
+        self.rejected = calibration.is_none();
+        if let Some(calibration) = calibration {
+            ctx.calibration = calibration;
+            ctx.battery.clear();
         }
     }
+}
+
+impl Screen for VoltageApp {
+    fn name(&self) -> &'static str {
+        "voltage"
+    }
+
+    fn init(&mut self, _ctx: &mut Context) {
+        *self = Self::new();
+    }
 
-    pub fn run<'a>(
-        mut self,
-        terminal: &mut Terminal<B>,
-        notification: &mut Notification,
-        button: &mut PinDriver<Gpio0, Input>,
-        adc_driver: &AdcDriver<'a, ADC1>,
-        adc_channel: &mut AdcChannelDriver<'a, Gpio34, &AdcDriver<'a, ADC1>>,
-    ) -> Result<()> {
-        button.enable_interrupt().unwrap();
-        loop {
-            if notification.wait(delay::NON_BLOCK).is_some() {
-                return Ok(());
+    fn handle_event(&mut self, event: InputEvent, ctx: &mut Context) -> Action {
+        let Some(reference) = self.reference else {
+            match event {
+                InputEvent::ShortPress => return Action::NextScreen,
+                InputEvent::DoublePress => return Action::PreviousScreen,
+                InputEvent::LongPress => {
+                    let step = REFERENCE_STEP_MILLIVOLTS;
+                    self.reference = ctx.battery.millivolts().map(|mv| mv / step * step);
+                    self.rejected = false;
+                }
+                InputEvent::Repeat => {}
+            }
+            return Action::Stay;
+        };
+        match event {
+            InputEvent::ShortPress => {
+                self.reference = Some(reference.saturating_add(REFERENCE_STEP_MILLIVOLTS));
+            }
+            InputEvent::DoublePress => {
+                self.reference = Some(reference.saturating_sub(REFERENCE_STEP_MILLIVOLTS));
             }
-            if let Ok(voltage) = adc_driver.read(adc_channel) {
-                terminal.draw(|frame| self.draw(frame, 2 * voltage))?;
+            InputEvent::LongPress => {
+                self.reference = None;
+                self.calibrate(reference, ctx);
             }
+            InputEvent::Repeat => {}
         }
+        Action::Stay
     }
 
-    fn draw(&mut self, frame: &mut Frame, voltage: u16) {
-        let [content_area, footer_area] =
-            Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(frame.area());
+    fn hints(&self) -> &'static str {
+        if self.reference.is_some() {
+            "[S1/2x] ±10mV [hold] save"
+        } else {
+            "[S1] next  [hold] calibrate"
+        }
+    }
+
+    fn draw(&self, frame: &mut Frame, area: Rect, ctx: &Context) {
+        let theme = &ctx.theme;
 
+        let title = match (self.reference, self.rejected) {
+            (Some(_), _) => Line::from("Enter reference voltage"),
+            (None, true) => Line::from(vec![
+                "Battery ".into(),
+                "calibration rejected".fg(theme.danger),
+            ]),
+            (None, false) => Line::from("Battery"),
+        };
         let block = Block::bordered()
             .border_type(BorderType::Rounded)
-            .padding(Padding::vertical(1))
-            .title("Battery voltage")
-            .border_style(Style::new().yellow());
-        let inner_area = block.inner(content_area);
-        frame.render_widget(block, content_area);
+            .title(title)
+            .border_style(theme.border);
+        let inner_area = block.inner(area);
+        frame.render_widget(block, area);
 
-        let voltage_text = format!("{:.2}V", voltage as f32 / 1000.0);
+        let state = ctx.battery.state();
+        let (big_text, details) = match (self.reference, state) {
+            (Some(reference), _) => {
+                let measured = state.map_or(0, |state| state.millivolts);
+                let details = format!(
+                    "reads {}V  gain {:.3}",
+                    volts(measured),
+                    ctx.calibration.gain
+                );
+                (volts(reference), Line::from(details))
+            }
+            (None, Some(state)) => {
+                let charge = match state.charge {
+                    ChargeState::Charging => "charging".fg(theme.good),
+                    ChargeState::Discharging => "discharging".fg(theme.footer),
+                    ChargeState::Unknown => "measuring".fg(theme.footer),
+                };
+                let voltage = format!("{}V  ", volts(state.millivolts));
+                (
+                    format!("{}%", state.percent),
+                    Line::from(vec![voltage.into(), charge]),
+                )
+            }
+            (None, None) => ("--%".to_string(), Line::from("no reading".fg(theme.footer))),
+        };
+
+        let [big_text_area, details_area] =
+            Layout::vertical([Constraint::Length(8), Constraint::Length(1)])
+                .flex(Flex::Center)
+                .areas(inner_area);
+        // Full-size big text glyphs are 8 cells wide.
+        let width = 8 * big_text.chars().count() as u16;
         let big_text = BigText::builder()
             .pixel_size(PixelSize::Full)
-            .style(Style::new().blue())
-            .lines(vec![voltage_text.into()])
+            .style(Style::new().fg(theme.secondary))
+            .lines(vec![big_text.into()])
             .build();
-        frame.render_widget(big_text, inner_area);
-
-        let footer = Line::raw("[S1] to change screen").centered().gray();
-        frame.render_widget(footer, footer_area);
+        frame.render_widget(
+            big_text,
+            center(
+                big_text_area,
+                Constraint::Length(width),
+                Constraint::Length(8),
+            ),
+        );
+        frame.render_widget(details.centered(), details_area);
     }
 }
+
+/// Formats millivolts as volts with two decimals, without the unit.
+fn volts(millivolts: u16) -> String {
+    format!("{:.2}", f32::from(millivolts) / 1000.0)
+}
@@ -0,0 +1,295 @@
+//! Renders every screen at the size of the real display and compares the
+//! result with the snapshots in `tests/snapshots`.
+//!
+//! After an intentional layout change, update them with `cargo insta review`.
+#![cfg(not(target_os = "espidf"))]
+
+use insta::assert_snapshot;
+use mousefood::prelude::*;
+use mousefood::ratatui::backend::TestBackend;
+use mousefood_esp32_demo::battery_history::BatteryHistoryApp;
+use mousefood_esp32_demo::calibration::Calibration;
+use mousefood_esp32_demo::chart::ChartApp;
+use mousefood_esp32_demo::clock::TimeSource;
+use mousefood_esp32_demo::crash::{CrashOverlay, CrashReport, CrashScreen};
+use mousefood_esp32_demo::gauge::GaugeApp;
+use mousefood_esp32_demo::input::InputEvent;
+use mousefood_esp32_demo::metrics::{MemoryStats, MetricsSource, ResetReason, SystemMetrics};
+use mousefood_esp32_demo::overlay::{LowBatteryOverlay, Overlay};
+use mousefood_esp32_demo::ratatui_logo::RatatuiLogoApp;
+use mousefood_esp32_demo::screen::{Context, Screen};
+use mousefood_esp32_demo::settings_screen::SettingsApp;
+use mousefood_esp32_demo::status_bar::{self, StatusBar};
+use mousefood_esp32_demo::system_monitor::SystemMonitorApp;
+use mousefood_esp32_demo::tabs::TabsApp;
+use mousefood_esp32_demo::voltage::VoltageApp;
+use std::time::{Duration, Instant};
+use time::{Date, Month, PrimitiveDateTime, Time};
+
+/// The 240x135 panel in cells of mousefood's default 6x10 font.
+const WIDTH: u16 = 240 / 6;
+const HEIGHT: u16 = 135 / 10;
+
+/// Draws `screen` above the status bar like the app does, as the third of five screens.
+fn draw(frame: &mut Frame, screen: &dyn Screen, ctx: &Context) {
+    let [screen_area, status_area] = status_bar::layout(frame.area());
+    screen.draw(frame, screen_area, ctx);
+    let status = StatusBar::new(screen.hints(), ctx).position(2, 5);
+    frame.render_widget(status, status_area);
+}
+
+fn render(screen: &dyn Screen, ctx: &Context) -> TestBackend {
+    let mut terminal = Terminal::new(TestBackend::new(WIDTH, HEIGHT)).unwrap();
+    terminal.draw(|frame| draw(frame, screen, ctx)).unwrap();
+    terminal.backend().clone()
+}
+
+fn render_with_overlay(screen: &dyn Screen, overlay: &dyn Overlay, ctx: &Context) -> TestBackend {
+    let mut terminal = Terminal::new(TestBackend::new(WIDTH, HEIGHT)).unwrap();
+    terminal
+        .draw(|frame| {
+            draw(frame, screen, ctx);
+            overlay.draw(frame, ctx);
+        })
+        .unwrap();
+    terminal.backend().clone()
+}
+
+fn press(screen: &mut dyn Screen, ctx: &mut Context) {
+    screen.handle_event(InputEvent::ShortPress, ctx);
+}
+
+#[test]
+fn ratatui_logo() {
+    let ctx = Context::default();
+    assert_snapshot!(render(&RatatuiLogoApp::new(), &ctx));
+}
+
+#[test]
+fn tabs() {
+    let mut ctx = Context::default();
+    // Noon, so the date can't change while the test runs.
+    let noon = PrimitiveDateTime::new(
+        Date::from_calendar_date(2025, Month::May, 23).unwrap(),
+        Time::from_hms(12, 0, 0).unwrap(),
+    );
+    ctx.clock
+        .set(noon.assume_utc(), TimeSource::Serial, Instant::now());
+    let mut app = TabsApp::with_temperatures(vec![50, 60, 70, 80, 89, 65]);
+    let paragraph = render(&app, &ctx);
+    assert_snapshot!("tabs_paragraph", render(&app, &ctx));
+    press(&mut app, &mut ctx);
+    assert_snapshot!("tabs_calendar", render(&app, &ctx));
+    app.handle_event(InputEvent::DoublePress, &mut ctx);
+    press(&mut app, &mut ctx);
+    assert_snapshot!("tabs_calendar_next_month", render(&app, &ctx));
+    app.handle_event(InputEvent::LongPress, &mut ctx);
+    press(&mut app, &mut ctx);
+    assert_snapshot!("tabs_barchart", render(&app, &ctx));
+
+    // Coming back starts on the first tab with the same temperatures.
+    let barchart = render(&app, &ctx);
+    app.init(&mut ctx);
+    assert_eq!(render(&app, &ctx).buffer(), paragraph.buffer());
+    press(&mut app, &mut ctx);
+    press(&mut app, &mut ctx);
+    assert_eq!(render(&app, &ctx).buffer(), barchart.buffer());
+}
+
+#[test]
+fn tabs_without_clock() {
+    let mut ctx = Context::default();
+    let mut app = TabsApp::with_temperatures(vec![50, 60, 70, 80, 89, 65]);
+    press(&mut app, &mut ctx);
+    assert_snapshot!("tabs_calendar_unset", render(&app, &ctx));
+}
+
+#[test]
+fn chart() {
+    let mut ctx = Context::default();
+    let mut app = ChartApp::new();
+    assert_snapshot!("chart", render(&app, &ctx));
+    press(&mut app, &mut ctx);
+    assert_snapshot!("chart_popup", render(&app, &ctx));
+
+    // The x labels stay put while the window slides.
+    let x_labels = |app: &ChartApp, ctx: &Context| {
+        let buffer = render(app, ctx).buffer().clone();
+        let row = HEIGHT - 3;
+        (0..WIDTH)
+            .map(|x| buffer[(x, row)].symbol().to_string())
+            .collect::<String>()
+    };
+    let before = x_labels(&app, &ctx);
+    app.tick(Duration::from_millis(1250), &mut ctx);
+    assert_eq!(x_labels(&app, &ctx), before);
+}
+
+#[test]
+fn settings() {
+    let mut ctx = Context::default();
+    let mut app = SettingsApp::new();
+    assert_snapshot!("settings", render(&app, &ctx));
+    app.handle_event(InputEvent::LongPress, &mut ctx);
+    app.handle_event(InputEvent::DoublePress, &mut ctx);
+    app.handle_event(InputEvent::DoublePress, &mut ctx);
+    assert_snapshot!("settings_editing", render(&app, &ctx));
+}
+
+#[test]
+fn crash() {
+    let report = CrashReport {
+        message: "called `Result::unwrap()` on an `Err` value: EspError(ESP_ERR_TIMEOUT)".into(),
+        location: Some("src/firmware.rs:201:54".into()),
+        uptime: Duration::from_secs(2 * 3600 + 5 * 60),
+    };
+    let mut terminal = Terminal::new(TestBackend::new(WIDTH, HEIGHT)).unwrap();
+    terminal
+        .draw(|frame| frame.render_widget(CrashScreen::new(&report), frame.area()))
+        .unwrap();
+    assert_snapshot!("crash", terminal.backend());
+
+    let mut ctx = Context::default();
+    let mut overlay = CrashOverlay::new(report);
+    let app = ChartApp::new();
+    assert_snapshot!(
+        "crash_after_reset",
+        render_with_overlay(&app, &overlay, &ctx)
+    );
+    assert!(overlay.handle_event(InputEvent::ShortPress, &mut ctx));
+    assert!(!overlay.handle_event(InputEvent::ShortPress, &mut ctx));
+}
+
+#[test]
+fn gauge() {
+    let ctx = Context::default();
+    assert_snapshot!(render(&GaugeApp::new(), &ctx));
+}
+
+/// Readings of a dual-core board, with the CPU load known from the second sample on.
+struct FakeMetrics {
+    samples: usize,
+    psram: bool,
+}
+
+impl MetricsSource for FakeMetrics {
+    fn sample(&mut self) -> SystemMetrics {
+        self.samples += 1;
+        SystemMetrics {
+            psram: self.psram.then_some(MemoryStats {
+                total: 4 * 1024 * 1024,
+                free: 3 * 1024 * 1024,
+                min_free: 3 * 1024 * 1024,
+                largest_block: 3 * 1024 * 1024,
+            }),
+            uptime: Duration::from_secs(3 * 3600 + 4 * 60 + 5),
+            reset_reason: ResetReason::Brownout,
+            cpu_load: if self.samples > 1 {
+                vec![72, 4]
+            } else {
+                Vec::new()
+            },
+            ..SystemMetrics::typical()
+        }
+    }
+}
+
+#[test]
+fn system_monitor() {
+    let mut ctx = Context::default();
+    let metrics = FakeMetrics {
+        samples: 0,
+        psram: false,
+    };
+    let mut app = SystemMonitorApp::new(Box::new(metrics));
+    app.init(&mut ctx);
+    assert_snapshot!("system_monitor_first_sample", render(&app, &ctx));
+    app.tick(Duration::from_secs(1), &mut ctx);
+    assert_snapshot!("system_monitor", render(&app, &ctx));
+
+    let metrics = FakeMetrics {
+        samples: 1,
+        psram: true,
+    };
+    let mut app = SystemMonitorApp::new(Box::new(metrics));
+    app.refresh();
+    assert_snapshot!("system_monitor_psram", render(&app, &ctx));
+}
+
+#[test]
+fn voltage() {
+    let mut ctx = Context::default();
+    assert_snapshot!("voltage_unknown", render(&VoltageApp::new(), &ctx));
+    ctx.calibration = Calibration::new(2.0);
+    let now = Instant::now();
+    for adc_millivolts in [1956, 1959, 0, 1957] {
+        ctx.push_battery_sample(adc_millivolts, now);
+    }
+    let mut app = VoltageApp::new();
+    assert_snapshot!("voltage", render(&app, &ctx));
+
+    app.handle_event(InputEvent::LongPress, &mut ctx);
+    press(&mut app, &mut ctx);
+    press(&mut app, &mut ctx);
+    assert_snapshot!("voltage_calibrating", render(&app, &ctx));
+    app.handle_event(InputEvent::LongPress, &mut ctx);
+    assert_eq!(ctx.battery.millivolts(), None);
+    ctx.push_battery_sample(1957, now);
+    assert_eq!(ctx.battery.millivolts(), Some(3930));
+
+    let mut ctx = Context::default();
+    for second in 0..60 {
+        ctx.battery
+            .push(4000 + second * 2, now + Duration::from_secs(second.into()));
+    }
+    assert_snapshot!("voltage_charging", render(&VoltageApp::new(), &ctx));
+}
+
+#[test]
+fn battery_history() {
+    let mut ctx = Context::default();
+    assert_snapshot!(
+        "battery_history_empty",
+        render(&BatteryHistoryApp::new(), &ctx)
+    );
+
+    // A slow discharge with a bump when the backlight was turned off.
+    let start = Instant::now();
+    for minute in 0..120 {
+        let bump = if (40..55).contains(&minute) { 12 } else { 0 };
+        let millivolts = 4050 - minute * 3 / 2 + bump;
+        ctx.battery_history.record(
+            millivolts,
+            start + Duration::from_secs(60 * u64::from(minute)),
+        );
+    }
+    let mut app = BatteryHistoryApp::new();
+    assert_snapshot!("battery_history_10m", render(&app, &ctx));
+    press(&mut app, &mut ctx);
+    press(&mut app, &mut ctx);
+    assert_snapshot!("battery_history_2h", render(&app, &ctx));
+}
+
+#[test]
+fn low_battery() {
+    let mut ctx = Context::default();
+    let mut overlay = LowBatteryOverlay::default();
+    let screen = GaugeApp::new();
+    let now = Instant::now();
+
+    ctx.battery.push(3470, now);
+    overlay.update(now, &mut ctx);
+    assert_snapshot!(
+        "low_battery_warning",
+        render_with_overlay(&screen, &overlay, &ctx)
+    );
+
+    ctx.battery.clear();
+    ctx.battery.push(3320, now);
+    overlay.update(now, &mut ctx);
+    overlay.update(now + Duration::from_secs(12), &mut ctx);
+    assert_snapshot!(
+        "low_battery_critical",
+        render_with_overlay(&screen, &overlay, &ctx)
+    );
+}